@@ -0,0 +1,134 @@
+use crate::{LtDisable, LtEnable};
+use core::{
+    any::Any,
+    marker::PhantomData,
+    fmt::{self, Debug, Formatter},
+};
+use alloc::boxed::Box;
+
+/// Lifetime-erased element of any type, for stacks whose
+/// levels have different types.
+///
+/// Wraps an element `E` which erases into some `S`, and can be
+/// downcast back into `<S as LtEnable<'a>>::Output`. An 
+/// `LtStack<AnyFrame<'static>>` can then hold different element
+/// types at each level.
+///
+/// Invariant in `'a`. Were it covariant, a frame's lifetime could
+/// be shortened, and a shorter-lived reference stored into it
+/// through interior mutability, then downcast back out with the
+/// original lifetime:
+///
+/// ```compile_fail
+/// use ltstack::{AnyFrame, LtDisable, LtEnable};
+/// use std::{cell::Cell, mem::transmute};
+///
+/// struct W<'a>(Cell<&'a String>);
+///
+/// impl<'a> LtDisable<W<'static>> for W<'a> {
+///     unsafe fn into_static(self) -> W<'static> { transmute(self) }
+/// }
+///
+/// impl<'o> LtEnable<'o> for W<'static> {
+///     type Output = W<'o>;
+///
+///     unsafe fn give_lifetime(self) -> W<'o> { transmute(self) }
+///
+///     unsafe fn give_lifetime_ref(&self) -> &W<'o> { transmute(self) }
+///
+///     unsafe fn give_lifetime_mut(&mut self) -> &mut W<'o> { transmute(self) }
+/// }
+///
+/// fn shrink<'s, 'l: 's>(frame: &AnyFrame<'l>, short: &'s String) {
+///     let frame: &AnyFrame<'s> = frame;
+///     frame.downcast_ref::<W<'static>>().unwrap().0.set(short);
+/// }
+/// ```
+pub struct AnyFrame<'a> {
+    inner: Box<dyn Any>,
+    p: PhantomData<fn(&'a ()) -> &'a ()>,
+}
+
+impl<'a> AnyFrame<'a> {
+    /// Box and erase an element.
+    pub fn new<E, S>(elem: E) -> Self
+    where
+        E: LtDisable<S> + 'a,
+        S: 'static,
+        S: for<'b> LtEnable<'b>,
+    {
+        AnyFrame {
+            inner: Box::new(unsafe { elem.into_static() }),
+            p: PhantomData,
+        }
+    }
+    
+    /// Whether the element was erased into `S`.
+    pub fn is<S>(&self) -> bool
+    where
+        S: 'static,
+        S: for<'b> LtEnable<'b>,
+    {
+        self.inner.is::<S>()
+    }
+    
+    /// Downcast into the element, if it was erased into `S`.
+    pub fn downcast<S>(self) -> Result<<S as LtEnable<'a>>::Output, Self>
+    where
+        S: 'static,
+        S: for<'b> LtEnable<'b>,
+    {
+        match self.inner.downcast::<S>() {
+            Ok(elem) => Ok(unsafe { elem.give_lifetime() }),
+            Err(inner) => Err(AnyFrame { inner, p: PhantomData }),
+        }
+    }
+    
+    /// Downcast by shared reference, if it was erased into `S`.
+    pub fn downcast_ref<S>(&self) -> Option<&<S as LtEnable<'a>>::Output>
+    where
+        S: 'static,
+        S: for<'b> LtEnable<'b>,
+    {
+        self.inner.downcast_ref::<S>()
+            .map(|elem| unsafe { elem.give_lifetime_ref() })
+    }
+    
+    /// Downcast by mutable reference, if it was erased into `S`.
+    pub fn downcast_mut<S>(&mut self) -> Option<&mut <S as LtEnable<'a>>::Output>
+    where
+        S: 'static,
+        S: for<'b> LtEnable<'b>,
+    {
+        self.inner.downcast_mut::<S>()
+            .map(|elem| unsafe { elem.give_lifetime_mut() })
+    }
+}
+
+impl<'a> LtDisable<AnyFrame<'static>> for AnyFrame<'a> {
+    unsafe fn into_static(self) -> AnyFrame<'static> {
+        AnyFrame { inner: self.inner, p: PhantomData }
+    }
+}
+
+impl<'o> LtEnable<'o> for AnyFrame<'static> {
+    type Output = AnyFrame<'o>;
+    
+    unsafe fn give_lifetime(self) -> AnyFrame<'o> {
+        AnyFrame { inner: self.inner, p: PhantomData }
+    }
+    
+    unsafe fn give_lifetime_ref(&self) -> &AnyFrame<'o> {
+        &*(self as *const AnyFrame<'static>).cast()
+    }
+    
+    unsafe fn give_lifetime_mut(&mut self) -> &mut AnyFrame<'o> {
+        &mut *(self as *mut AnyFrame<'static>).cast()
+    }
+}
+
+impl<'a> Debug for AnyFrame<'a> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("AnyFrame { .. }")
+    }
+}
@@ -1,33 +1,117 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
 extern crate pinvec;
 
+#[cfg(feature = "derive")]
+pub use ltstack_derive::LtStackElem;
+
 #[cfg(test)]
 mod tests;
 
-use std::{
+/// Heterogeneous stack elements.
+pub mod any;
+
+/// Stacks of shared borrows.
+pub mod shared;
+
+pub use self::{
+    any::AnyFrame,
+    shared::{LtStackRef, RefBorrower},
+};
+
+use core::{
     cell::UnsafeCell,
-    pin::Pin,
+    ops::{Deref, DerefMut},
+    iter::Rev,
+    marker::PhantomData,
+    fmt::{self, Display, Formatter},
+    mem,
 };
+#[cfg(feature = "std")]
+use std::error::Error;
 use pinvec::PinVec;
 
-// TODO: blanket-impl lt stuff for static types
-
+/// Type which can have its lifetime erased into `S`.
 pub trait LtDisable<S>: Sized
 where
     S: 'static,
     S: for<'a> LtEnable<'a>,
 {
+    /// Erase the lifetime.
+    ///
+    /// # Safety
+    ///
+    /// The result must not be used beyond the original
+    /// lifetime, except to be re-enabled within it.
     unsafe fn into_static(self) -> S;
 }
 
+/// Lifetime-erased type which can be given back a lifetime.
 pub trait LtEnable<'o>: Sized {
     type Output: 'o;
     
+    /// Re-attach a lifetime by value.
+    ///
+    /// # Safety
+    ///
+    /// `'o` must not outlive the lifetime which was erased.
     unsafe fn give_lifetime(self) -> Self::Output;
     
-    unsafe fn give_lifetime_ref<'s>(&'s self) -> &'s Self::Output;
+    /// Re-attach a lifetime by shared reference.
+    ///
+    /// # Safety
+    ///
+    /// `'o` must not outlive the lifetime which was erased.
+    unsafe fn give_lifetime_ref(&self) -> &Self::Output;
+    
+    /// Re-attach a lifetime by mutable reference.
+    ///
+    /// # Safety
+    ///
+    /// `'o` must not outlive the lifetime which was erased.
+    unsafe fn give_lifetime_mut(&mut self) -> &mut Self::Output;
+}
+
+/// Wrapper which gives a `'static` type identity impls of
+/// `LtDisable` and `LtEnable`.
+///
+/// A blanket impl over all `'static` types would overlap with
+/// the impls of lifetime-carrying types, as coherence does not
+/// consider lifetimes.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Static<T>(pub T);
+
+impl<T> Static<T> {
+    pub fn into_inner(self) -> T { self.0 }
+}
+
+impl<T> From<T> for Static<T> {
+    fn from(inner: T) -> Self { Static(inner) }
+}
+
+impl<T> Deref for Static<T> {
+    type Target = T;
+    
+    fn deref(&self) -> &T { &self.0 }
+}
+
+impl<T> DerefMut for Static<T> {
+    fn deref_mut(&mut self) -> &mut T { &mut self.0 }
+}
+
+impl<T: 'static> LtDisable<Static<T>> for Static<T> {
+    unsafe fn into_static(self) -> Static<T> { self }
+}
+
+impl<'o, T: 'static> LtEnable<'o> for Static<T> {
+    type Output = Static<T>;
+    
+    unsafe fn give_lifetime(self) -> Static<T> { self }
+    
+    unsafe fn give_lifetime_ref(&self) -> &Static<T> { self }
     
-    unsafe fn give_lifetime_mut<'s>(&'s mut self) -> &'s mut Self::Output;
+    unsafe fn give_lifetime_mut(&mut self) -> &mut Static<T> { self }
 }
 
 pub trait Borrower<'l, S>: Sized
@@ -41,6 +125,64 @@ where
     fn apply(self, top: &'l mut <S as LtEnable<'l>>::Output) -> Self::Iterator;
 }
 
+/// Fallible version of `Borrower`.
+///
+/// The error type is a parameter rather than an associated
+/// type, so that it cannot depend on `'l`.
+pub trait TryBorrower<'l, S, E>: Sized
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    type Borrowed: LtDisable<S> + 'l;
+    type Iterator: IntoIterator<Item=Result<Self::Borrowed, E>>;
+    
+    fn try_apply(self, top: &'l mut <S as LtEnable<'l>>::Output) 
+        -> Result<Self::Iterator, E>;
+}
+
+/// Failure of `LtStack::try_grow`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrowError<E> {
+    /// The stack was empty, so there was nothing to borrow
+    /// from. The stack is unchanged.
+    Empty,
+    /// `TryBorrower::try_apply` failed. The stack is unchanged.
+    Apply(E),
+    /// The iterator yielded an error. The elements yielded 
+    /// before it remain pushed.
+    Elem {
+        pushed: usize,
+        error: E,
+    },
+}
+
+impl<E: Display> Display for GrowError<E> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GrowError::Empty => f.write_str("grow on empty LtStack"),
+            GrowError::Apply(e) => Display::fmt(e, f),
+            GrowError::Elem { pushed, error } => write!(f,
+                "{} (after pushing {} elements)", error, pushed),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: Error + 'static> Error for GrowError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GrowError::Empty => None,
+            GrowError::Apply(e) => Some(e),
+            GrowError::Elem { error, .. } => Some(error),
+        }
+    }
+}
+
+/// Stack of elements which may borrow from the elements below
+/// them.
+///
+/// When dropped, elements are dropped top-first.
 pub struct LtStack<'base, S>
 where
     S: 'static,
@@ -48,7 +190,7 @@ where
 {
     vec: PinVec<UnsafeCell<S>>,
     
-    p: std::marker::PhantomData<&'base ()>,
+    p: PhantomData<&'base ()>,
 }
 
 impl<'base, S> LtStack<'base, S>
@@ -59,7 +201,7 @@ where
     // == constructors ==
 
     pub fn empty() -> Self {
-        LtStack { vec: PinVec::default(), p: std::marker::PhantomData }
+        LtStack { vec: PinVec::default(), p: PhantomData }
     }
     
     // == mutators ==
@@ -78,6 +220,13 @@ where
         }
     }
     
+    /// Push elements borrowed from the top element.
+    ///
+    /// Return false if the stack is empty.
+    ///
+    /// If `f` or its iterator panics, elements pushed so far are
+    /// dropped before unwinding continues, leaving the stack as 
+    /// it was.
     pub fn grow<F>(&mut self, f: F) -> bool
     where
         F: for<'l> Borrower<'l, S>
@@ -88,15 +237,120 @@ where
         };
         
         unsafe {
-            //let top = &mut self.vec[len - 1]; TODO
-            let top = Pin::into_inner_unchecked(
-                self.vec.idx_mut(len - 1));
+            let vec: *mut PinVec<UnsafeCell<S>> = &mut self.vec;
+            let rollback = PanicRollback { vec, len };
+            
+            let top = &(&*vec)[len - 1];
             let top = (&mut *top.get()).give_lifetime_mut();
         
             let iter = f.apply(top);
             for elem in iter {
-                self.vec.push(UnsafeCell::new(elem.into_static()));
+                (*vec).push(UnsafeCell::new(elem.into_static()));
             }
+            
+            rollback.disarm();
+        }
+        
+        true
+    }
+    
+    /// Drop elements from the top until only `len` remain.
+    ///
+    /// Elements are dropped top-first, so no element outlives one
+    /// it borrows from. Does nothing if there are already no more
+    /// than `len` elements.
+    pub fn pop_to(&mut self, len: usize) {
+        self.vec.truncate(len);
+    }
+    
+    /// Drop elements pushed since the checkpoint was taken.
+    pub fn truncate(&mut self, checkpoint: Checkpoint) {
+        self.pop_to(checkpoint.len);
+    }
+    
+    /// Guard which derefs to this stack, and truncates it back to
+    /// its current depth when dropped.
+    pub fn scope(&mut self) -> Scope<'_, 'base, S> {
+        let checkpoint = self.checkpoint();
+        Scope { stack: self, checkpoint }
+    }
+    
+    /// Fallible version of `grow`.
+    ///
+    /// If the iterator yields an error, elements yielded before
+    /// it remain pushed, and the rest of the iterator is dropped.
+    /// Panics are handled like in `grow`.
+    pub fn try_grow<F, E>(&mut self, f: F) -> Result<(), GrowError<E>>
+    where
+        F: for<'l> TryBorrower<'l, S, E>
+    {
+        let len = match self.vec.len() {
+            0 => return Err(GrowError::Empty),
+            l => l,
+        };
+        
+        unsafe {
+            let vec: *mut PinVec<UnsafeCell<S>> = &mut self.vec;
+            let rollback = PanicRollback { vec, len };
+            
+            let top = &(&*vec)[len - 1];
+            let top = (&mut *top.get()).give_lifetime_mut();
+            
+            let iter = match f.try_apply(top) {
+                Ok(iter) => iter,
+                Err(e) => {
+                    rollback.disarm();
+                    return Err(GrowError::Apply(e));
+                },
+            };
+            let mut iter = iter.into_iter().enumerate();
+            while let Some((pushed, elem)) = iter.next() {
+                match elem {
+                    Ok(elem) => (*vec).push(UnsafeCell::new(elem.into_static())),
+                    Err(error) => {
+                        // drop the rest of the iterator while still
+                        // able to roll back
+                        drop(iter);
+                        rollback.disarm();
+                        return Err(GrowError::Elem { pushed, error });
+                    },
+                }
+            }
+            
+            rollback.disarm();
+        }
+        
+        Ok(())
+    }
+    
+    /// Like `grow`, but with a closure.
+    ///
+    /// Rather than returning an iterator, whose type would depend
+    /// on `'l` and thus could not be inferred for a closure, the 
+    /// closure pushes the new elements through a `Pusher`. Panics
+    /// are handled like in `grow`.
+    pub fn grow_with<F>(&mut self, f: F) -> bool
+    where
+        F: for<'l> FnOnce(
+            &'l mut <S as LtEnable<'l>>::Output,
+            &mut Pusher<'l, S>,
+        ),
+    {
+        let len = match self.vec.len() {
+            0 => return false,
+            l => l,
+        };
+        
+        unsafe {
+            let vec: *mut PinVec<UnsafeCell<S>> = &mut self.vec;
+            let rollback = PanicRollback { vec, len };
+            
+            let top = &(&*vec)[len - 1];
+            let top = (&mut *top.get()).give_lifetime_mut();
+            
+            f(top, &mut Pusher { vec, p: PhantomData });
+            
+            rollback.disarm();
         }
         
         true
@@ -106,18 +360,244 @@ where
     
     pub fn len(&self) -> usize { self.vec.len() }
     
-    pub fn top<'s>(&'s mut self) -> Option<&mut <S as LtEnable<'s>>::Output> {
+    pub fn is_empty(&self) -> bool { self.vec.is_empty() }
+    
+    /// Record the current depth, to later `truncate` back to.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { len: self.vec.len() }
+    }
+    
+    pub fn top<'s>(&'s mut self) -> Option<&'s mut <S as LtEnable<'s>>::Output> {
         match self.vec.len() {
             0 => None,
             l => Some(unsafe {
-                let elem = Pin::into_inner_unchecked(
-                    self.vec.idx_mut(l - 1));
-                //let elem = &mut self.vec[l - 1]; TODO
+                let elem = &self.vec[l - 1];
                 (&mut *elem.get()).give_lifetime_mut()
             })
         }
     }
     
-    // iter (immutable), index (immutable)
+    /// Get an element by index, counting from the bottom.
+    ///
+    /// # Safety
+    ///
+    /// No element above `index` may hold a mutable borrow derived
+    /// from the element at `index`, including reborrows of mutable
+    /// references it holds. Reading the element would invalidate
+    /// such a borrow. `LtStackRef` upholds this by construction.
+    pub unsafe fn get<'s>(&'s self, index: usize) -> Option<&'s <S as LtEnable<'s>>::Output> {
+        if index >= self.vec.len() { return None; }
+        let elem = &self.vec[index];
+        Some((&*elem.get()).give_lifetime_ref())
+    }
+    
+    /// Iterate from bottom to top.
+    ///
+    /// # Safety
+    ///
+    /// No element may hold a mutable borrow derived from an
+    /// element below it. See `get`.
+    pub unsafe fn iter(&self) -> Iter<'_, S> {
+        Iter { vec: &self.vec, front: 0, back: self.vec.len() }
+    }
+    
+    /// Iterate from top to bottom.
+    ///
+    /// # Safety
+    ///
+    /// See `iter`.
+    pub unsafe fn iter_rev(&self) -> Rev<Iter<'_, S>> {
+        self.iter().rev()
+    }
+}
+
+/// Depth of an `LtStack` at some point in time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Checkpoint {
+    len: usize,
 }
 
+impl Checkpoint {
+    /// Stack length when the checkpoint was taken.
+    pub fn depth(self) -> usize { self.len }
+}
+
+/// Guard which truncates an `LtStack` on drop.
+///
+/// See `LtStack::scope`.
+pub struct Scope<'s, 'base, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    stack: &'s mut LtStack<'base, S>,
+    checkpoint: Checkpoint,
+}
+
+impl<'s, 'base, S> Scope<'s, 'base, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    /// The depth which will be truncated to.
+    pub fn checkpoint(&self) -> Checkpoint { self.checkpoint }
+}
+
+impl<'s, 'base, S> Deref for Scope<'s, 'base, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    type Target = LtStack<'base, S>;
+    
+    fn deref(&self) -> &LtStack<'base, S> { self.stack }
+}
+
+impl<'s, 'base, S> DerefMut for Scope<'s, 'base, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    fn deref_mut(&mut self) -> &mut LtStack<'base, S> { self.stack }
+}
+
+impl<'s, 'base, S> Drop for Scope<'s, 'base, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    fn drop(&mut self) {
+        self.stack.truncate(self.checkpoint);
+    }
+}
+
+/// Handle for pushing elements borrowed from the top of an
+/// `LtStack`, within `LtStack::grow_with`.
+pub struct Pusher<'l, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    vec: *mut PinVec<UnsafeCell<S>>,
+    
+    p: PhantomData<&'l mut ()>,
+}
+
+impl<'l, S> Pusher<'l, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    pub fn push<E>(&mut self, elem: E)
+    where
+        E: LtDisable<S> + 'l,
+    {
+        unsafe { (*self.vec).push(UnsafeCell::new(elem.into_static())) };
+    }
+}
+
+impl<'l, S, E> Extend<E> for Pusher<'l, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>,
+    E: LtDisable<S> + 'l,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item=E>,
+    {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
+/// Guard which, unless disarmed, pops a stack's elements back 
+/// down to a length when dropped.
+///
+/// Disarmed once growing completes, so that it only fires when 
+/// unwinding, and elements pushed while growing from the top 
+/// element are dropped before unwinding ends that borrow.
+pub(crate) struct PanicRollback<S> {
+    pub(crate) vec: *mut PinVec<UnsafeCell<S>>,
+    pub(crate) len: usize,
+}
+
+impl<S> PanicRollback<S> {
+    /// Keep the pushed elements.
+    pub(crate) fn disarm(self) {
+        mem::forget(self);
+    }
+}
+
+impl<S> Drop for PanicRollback<S> {
+    fn drop(&mut self) {
+        unsafe { (*self.vec).truncate(self.len); }
+    }
+}
+
+/// Shared iterator over an `LtStack`.
+///
+/// Only constructed by `LtStack::iter`, whose caller guarantees 
+/// that reading every element is sound.
+pub struct Iter<'s, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    vec: &'s PinVec<UnsafeCell<S>>,
+    front: usize,
+    back: usize,
+}
+
+impl<'s, S> Iter<'s, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    fn elem(&self, index: usize) -> &'s <S as LtEnable<'s>>::Output {
+        unsafe {
+            let elem = &self.vec[index];
+            (&*elem.get()).give_lifetime_ref()
+        }
+    }
+}
+
+impl<'s, S> Iterator for Iter<'s, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    type Item = &'s <S as LtEnable<'s>>::Output;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back { return None; }
+        let elem = self.elem(self.front);
+        self.front += 1;
+        Some(elem)
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.back - self.front;
+        (n, Some(n))
+    }
+}
+
+impl<'s, S> DoubleEndedIterator for Iter<'s, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back { return None; }
+        self.back -= 1;
+        Some(self.elem(self.back))
+    }
+}
+
+impl<'s, S> ExactSizeIterator for Iter<'s, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{}
+
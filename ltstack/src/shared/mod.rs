@@ -0,0 +1,152 @@
+use crate::{LtDisable, LtEnable, LtStack, Pusher, Checkpoint, Iter, PanicRollback};
+use core::{
+    cell::UnsafeCell,
+    iter::Rev,
+    marker::PhantomData,
+};
+use pinvec::PinVec;
+
+/// Version of `Borrower` which borrows the top element shared.
+pub trait RefBorrower<'l, S>: Sized
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    type Borrowed: LtDisable<S> + 'l;
+    type Iterator: IntoIterator<Item=Self::Borrowed>;
+    
+    fn apply(self, top: &'l <S as LtEnable<'l>>::Output) -> Self::Iterator;
+}
+
+/// Stack of elements which borrow shared from each other.
+///
+/// Since higher elements only hold shared borrows of lower 
+/// elements, lower elements can still be read while higher
+/// elements exist.
+pub struct LtStackRef<'base, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    stack: LtStack<'base, S>,
+}
+
+impl<'base, S> LtStackRef<'base, S>
+where
+    S: 'static,
+    S: for<'a> LtEnable<'a>
+{
+    // == constructors ==
+    
+    pub fn empty() -> Self {
+        LtStackRef { stack: LtStack::empty() }
+    }
+    
+    // == mutators ==
+    
+    pub fn push<E>(&mut self, elem: E)
+    where
+        E: LtDisable<S> + 'base,
+    {
+        self.stack.push(elem);
+    }
+    
+    pub fn pop<'s>(&'s mut self) -> Option<<S as LtEnable<'s>>::Output> {
+        self.stack.pop()
+    }
+    
+    pub fn pop_to(&mut self, len: usize) {
+        self.stack.pop_to(len);
+    }
+    
+    pub fn truncate(&mut self, checkpoint: Checkpoint) {
+        self.stack.truncate(checkpoint);
+    }
+    
+    /// Push elements borrowed shared from the top element.
+    ///
+    /// Return false if the stack is empty. Panics are handled like
+    /// in `LtStack::grow`.
+    pub fn grow<F>(&mut self, f: F) -> bool
+    where
+        F: for<'l> RefBorrower<'l, S>
+    {
+        let len = match self.stack.vec.len() {
+            0 => return false,
+            l => l,
+        };
+        
+        unsafe {
+            let vec: *mut PinVec<UnsafeCell<S>> = &mut self.stack.vec;
+            let rollback = PanicRollback { vec, len };
+            
+            let top = &(&*vec)[len - 1];
+            let top = (&*top.get()).give_lifetime_ref();
+            
+            let iter = f.apply(top);
+            for elem in iter {
+                (*vec).push(UnsafeCell::new(elem.into_static()));
+            }
+            
+            rollback.disarm();
+        }
+        
+        true
+    }
+    
+    /// Like `grow`, but with a closure.
+    pub fn grow_with<F>(&mut self, f: F) -> bool
+    where
+        F: for<'l> FnOnce(
+            &'l <S as LtEnable<'l>>::Output,
+            &mut Pusher<'l, S>,
+        ),
+    {
+        let len = match self.stack.vec.len() {
+            0 => return false,
+            l => l,
+        };
+        
+        unsafe {
+            let vec: *mut PinVec<UnsafeCell<S>> = &mut self.stack.vec;
+            let rollback = PanicRollback { vec, len };
+            
+            let top = &(&*vec)[len - 1];
+            let top = (&*top.get()).give_lifetime_ref();
+            
+            f(top, &mut Pusher { vec, p: PhantomData });
+            
+            rollback.disarm();
+        }
+        
+        true
+    }
+    
+    // == accessors ==
+    
+    pub fn len(&self) -> usize { self.stack.len() }
+    
+    pub fn is_empty(&self) -> bool { self.stack.is_empty() }
+    
+    pub fn checkpoint(&self) -> Checkpoint { self.stack.checkpoint() }
+    
+    pub fn top<'s>(&'s self) -> Option<&'s <S as LtEnable<'s>>::Output> {
+        match self.stack.len() {
+            0 => None,
+            l => self.get(l - 1),
+        }
+    }
+    
+    // elements only ever borrow shared from the elements below
+    // them, so reading any element is sound
+    
+    pub fn get<'s>(&'s self, index: usize) -> Option<&'s <S as LtEnable<'s>>::Output> {
+        unsafe { self.stack.get(index) }
+    }
+    
+    /// Iterate from bottom to top.
+    pub fn iter(&self) -> Iter<'_, S> { unsafe { self.stack.iter() } }
+    
+    /// Iterate from top to bottom.
+    pub fn iter_rev(&self) -> Rev<Iter<'_, S>> { unsafe { self.stack.iter_rev() } }
+}
@@ -1,5 +1,11 @@
 
-use crate::{LtDisable, LtEnable, LtStack, Borrower};
+use crate::{LtDisable, LtEnable, LtStack, Borrower, TryBorrower, GrowError, Static, AnyFrame,
+    LtStackRef, RefBorrower};
+use pinvec::test_util::DropLog;
+use std::{
+    any::Any,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
 
 #[derive(Debug)]
 struct Foo<'a>(&'a mut u32);
@@ -48,4 +54,346 @@ fn basic() {
         *r += 1;
     }
     assert_eq!(n, 100)
-}
\ No newline at end of file
+}
+
+#[test]
+fn static_elems() {
+    let mut stack = LtStack::empty();
+    stack.push(Static(1u32));
+    stack.push(Static(2u32));
+    **stack.top().unwrap() += 10;
+    assert_eq!(stack.pop(), Some(Static(12)));
+    assert_eq!(stack.pop(), Some(Static(1)));
+    assert_eq!(stack.pop(), None);
+    
+    let mut stack = LtStack::empty();
+    stack.push(Static(String::from("a")));
+    
+    struct B;
+    impl<'l> Borrower<'l, Static<String>> for B {
+        type Borrowed = Static<String>;
+        type Iterator = Option<Static<String>>;
+        
+        fn apply(self, top: &'l mut Static<String>) -> Self::Iterator {
+            Some(Static(format!("{}a", top.0)))
+        }
+    }
+    
+    stack.grow(B);
+    stack.grow(B);
+    assert_eq!(stack.len(), 3);
+    assert_eq!(&**stack.top().unwrap(), "aaa");
+    
+    let mut stack = LtStack::empty();
+    stack.push(Static(Box::new(7i64) as Box<dyn Any>));
+    let top = stack.top().unwrap();
+    assert_eq!(top.downcast_ref::<i64>(), Some(&7));
+}
+
+#[test]
+fn shared_access() {
+    let mut stack = LtStack::empty();
+    for i in 0..200u32 {
+        stack.push(Static(i));
+    }
+    
+    // no element borrows from another
+    unsafe {
+        assert_eq!(stack.iter().len(), 200);
+        for (i, elem) in stack.iter().enumerate() {
+            assert_eq!(**elem, i as u32);
+        }
+        for (i, elem) in stack.iter_rev().enumerate() {
+            assert_eq!(**elem, 199 - i as u32);
+        }
+        assert_eq!(stack.get(150), Some(&Static(150)));
+        assert_eq!(stack.get(200), None);
+        
+        let mut iter = stack.iter();
+        assert_eq!(iter.next(), Some(&Static(0)));
+        assert_eq!(iter.next_back(), Some(&Static(199)));
+        assert_eq!(iter.len(), 198);
+    }
+}
+
+#[test]
+fn grow_with() {
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Foo(&mut n));
+    for _ in 0..99 {
+        assert!(stack.grow_with(|top, frames| frames.push(Foo(top.0))));
+    }
+    assert_eq!(stack.len(), 100);
+    while let Some(Foo(r)) = stack.pop() {
+        *r += 1;
+    }
+    assert_eq!(n, 100);
+    
+    let mut stack = LtStack::empty();
+    stack.push(Static(0u32));
+    stack.grow_with(|top, frames| 
+        frames.extend((1..4).map(|i| Static(top.0 + i))));
+    assert_eq!(stack.len(), 4);
+    assert_eq!(stack.pop(), Some(Static(3)));
+    
+    let mut stack: LtStack<Static<u32>> = LtStack::empty();
+    assert!(!stack.grow_with(|_, _| panic!()));
+}
+
+#[test]
+fn try_grow() {
+    struct B(u32);
+    impl<'l> TryBorrower<'l, Static<u32>, &'static str> for B {
+        type Borrowed = Static<u32>;
+        type Iterator = Vec<Result<Static<u32>, &'static str>>;
+        
+        fn try_apply(self, top: &'l mut Static<u32>) 
+            -> Result<Self::Iterator, &'static str>
+        {
+            match self.0 {
+                0 => Err("apply"),
+                n => Ok((0..n)
+                    .map(|i| if i == 2 { Err("elem") } else { Ok(Static(top.0 + i)) })
+                    .collect()),
+            }
+        }
+    }
+    
+    let mut stack = LtStack::empty();
+    assert_eq!(stack.try_grow(B(1)), Err(GrowError::Empty));
+    
+    stack.push(Static(10));
+    assert_eq!(stack.try_grow(B(0)), Err(GrowError::Apply("apply")));
+    assert_eq!(stack.len(), 1);
+    
+    assert_eq!(stack.try_grow(B(2)), Ok(()));
+    assert_eq!(stack.len(), 3);
+    
+    assert_eq!(stack.try_grow(B(5)), Err(GrowError::Elem { 
+        pushed: 2, 
+        error: "elem",
+    }));
+    assert_eq!(stack.len(), 5);
+    assert_eq!(stack.pop(), Some(Static(12)));
+}
+
+#[test]
+fn checkpoint_truncate() {
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Foo(&mut n));
+    
+    let checkpoint = stack.checkpoint();
+    assert_eq!(checkpoint.depth(), 1);
+    for _ in 0..50 {
+        stack.grow_with(|top, frames| frames.push(Foo(top.0)));
+    }
+    *stack.top().unwrap().0 += 1;
+    stack.pop_to(30);
+    assert_eq!(stack.len(), 30);
+    stack.pop_to(100);
+    assert_eq!(stack.len(), 30);
+    stack.truncate(checkpoint);
+    assert_eq!(stack.len(), 1);
+    
+    {
+        let mut scope = stack.scope();
+        for _ in 0..50 {
+            scope.grow_with(|top, frames| frames.push(Foo(top.0)));
+        }
+        *scope.top().unwrap().0 += 1;
+        assert_eq!(scope.len(), 51);
+    }
+    assert_eq!(stack.len(), 1);
+    
+    let Foo(r) = stack.pop().unwrap();
+    *r += 1;
+    assert_eq!(n, 3);
+}
+
+#[test]
+fn heterogeneous() {
+    let mut doc = (String::from("title"), 0u32);
+    let mut stack = LtStack::empty();
+    stack.push(AnyFrame::new(Foo(&mut doc.1)));
+    stack.push(AnyFrame::new(Static(doc.0.clone())));
+    
+    let top = stack.top().unwrap();
+    assert!(top.is::<Static<String>>());
+    assert!(top.downcast_mut::<Foo<'static>>().is_none());
+    top.downcast_mut::<Static<String>>().unwrap().push('!');
+    let top = stack.pop().unwrap();
+    assert_eq!(top.downcast::<Static<String>>().unwrap().0, "title!");
+    
+    stack.grow_with(|top, frames| {
+        let foo = top.downcast_mut::<Foo<'static>>().unwrap();
+        *foo.0 += 1;
+        frames.push(AnyFrame::new(Foo(foo.0)));
+    });
+    assert_eq!(stack.len(), 2);
+    // the top element has nothing above it
+    assert_eq!(*unsafe { stack.get(1) }.unwrap()
+        .downcast_ref::<Foo<'static>>().unwrap().0, 1);
+    
+    let top = stack.pop().unwrap();
+    let top = top.downcast::<Static<String>>().unwrap_err();
+    let Foo(r) = top.downcast::<Foo<'static>>().unwrap();
+    *r += 1;
+    drop(stack);
+    assert_eq!(doc.1, 2);
+}
+
+#[derive(Debug)]
+struct Node {
+    val: u32,
+    children: Vec<Node>,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct NodeRef<'a>(&'a Node);
+
+impl<'a> LtDisable<NodeRef<'static>> for NodeRef<'a> {
+    unsafe fn into_static(self) -> NodeRef<'static> {
+        std::mem::transmute(self)
+    }
+}
+
+impl<'o> LtEnable<'o> for NodeRef<'static> {
+    type Output = NodeRef<'o>;
+    
+    unsafe fn give_lifetime(self) -> NodeRef<'o> { self }
+    
+    unsafe fn give_lifetime_ref(&self) -> &NodeRef<'o> { self }
+    
+    unsafe fn give_lifetime_mut(&mut self) -> &mut NodeRef<'o> { 
+        std::mem::transmute(self)
+    }
+}
+
+#[test]
+fn shared_stack() {
+    let tree = Node {
+        val: 0,
+        children: vec![
+            Node {
+                val: 1,
+                children: vec![Node { val: 2, children: Vec::new() }],
+            },
+            Node { val: 3, children: Vec::new() },
+        ],
+    };
+    
+    let mut stack = LtStackRef::empty();
+    stack.push(NodeRef(&tree));
+    
+    struct FirstChild;
+    impl<'l> RefBorrower<'l, NodeRef<'static>> for FirstChild {
+        type Borrowed = NodeRef<'l>;
+        type Iterator = Option<NodeRef<'l>>;
+        
+        fn apply(self, top: &'l NodeRef<'l>) -> Option<NodeRef<'l>> {
+            top.0.children.first().map(NodeRef)
+        }
+    }
+    
+    assert!(stack.grow(FirstChild));
+    assert!(stack.grow_with(|top, frames| 
+        frames.extend(top.0.children.iter().map(NodeRef))));
+    assert_eq!(stack.len(), 3);
+    
+    // lower frames stay readable while higher frames exist
+    let bottom = stack.get(0).unwrap();
+    let top = stack.top().unwrap();
+    assert_eq!(bottom.0.val + top.0.val, 2);
+    let vals: Vec<u32> = stack.iter().map(|n| n.0.val).collect();
+    assert_eq!(vals, [0, 1, 2]);
+    
+    assert_eq!(stack.pop().map(|n| n.0.val), Some(2));
+    stack.pop();
+    stack.grow_with(|top, frames| 
+        frames.extend(top.0.children.iter().map(NodeRef)));
+    let vals: Vec<u32> = stack.iter_rev().map(|n| n.0.val).collect();
+    assert_eq!(vals, [3, 1, 0]);
+}
+
+#[test]
+fn grow_panic_rollback() {
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Foo(&mut n));
+    
+    struct B;
+    impl<'l> Borrower<'l, Foo<'static>> for B {
+        type Borrowed = Foo<'l>;
+        type Iterator = Box<dyn Iterator<Item=Foo<'l>> + 'l>;
+        
+        fn apply(self, top: &'l mut Foo<'l>) -> Self::Iterator {
+            Box::new(Some(Foo(top.0)).into_iter()
+                .chain((0..1).map(|_| panic!("B"))))
+        }
+    }
+    
+    let result = catch_unwind(AssertUnwindSafe(|| stack.grow(B)));
+    assert!(result.is_err());
+    assert_eq!(stack.len(), 1);
+    
+    let result = catch_unwind(AssertUnwindSafe(|| 
+        stack.grow_with(|top, frames| {
+            frames.push(Foo(top.0));
+            panic!("grow_with");
+        })));
+    assert!(result.is_err());
+    assert_eq!(stack.len(), 1);
+    
+    // stack remains usable
+    stack.grow_with(|top, frames| frames.push(Foo(top.0)));
+    *stack.top().unwrap().0 += 1;
+    stack.pop_to(0);
+    assert_eq!(n, 1);
+    
+    struct T;
+    impl<'l> TryBorrower<'l, Static<u32>, ()> for T {
+        type Borrowed = Static<u32>;
+        type Iterator = Box<dyn Iterator<Item=Result<Static<u32>, ()>>>;
+        
+        fn try_apply(self, _: &'l mut Static<u32>) -> Result<Self::Iterator, ()> {
+            Ok(Box::new((0..3).map(|i| match i {
+                2 => panic!("T"),
+                i => Ok(Static(i)),
+            })))
+        }
+    }
+    
+    let mut stack = LtStack::empty();
+    stack.push(Static(0));
+    let result = catch_unwind(AssertUnwindSafe(|| stack.try_grow(T)));
+    assert!(result.is_err());
+    assert_eq!(stack.len(), 1);
+    
+    let tree = Node { val: 0, children: Vec::new() };
+    let mut stack = LtStackRef::empty();
+    stack.push(NodeRef(&tree));
+    let result = catch_unwind(AssertUnwindSafe(|| 
+        stack.grow_with(|top, frames| {
+            frames.push(*top);
+            frames.push(*top);
+            panic!("LtStackRef::grow_with");
+        })));
+    assert!(result.is_err());
+    assert_eq!(stack.len(), 1);
+    assert_eq!(stack.top().unwrap().0.val, 0);
+}
+
+#[test]
+fn drop_top_first() {
+    // enough to span several buffers
+    let log = DropLog::new();
+    let mut stack = LtStack::empty();
+    log.fill(&mut stack, 0..1000, |stack, elem| stack.push(Static(elem)));
+    stack.pop_to(900);
+    log.assert_dropped_rev(900..1000);
+    
+    drop(stack);
+    log.assert_dropped_rev(0..900);
+}
@@ -0,0 +1,271 @@
+//! Exercises the unsafe parts of `LtStack`. Sized to run under 
+//! Miri, with either the stacked or tree borrows model:
+//!
+//!     cargo +nightly miri test -p ltstack --test soundness
+
+use ltstack::{LtDisable, LtEnable, LtStack, Borrower, TryBorrower, GrowError, Static};
+use pinvec::test_util::DropLog;
+use std::{
+    rc::Rc,
+    marker::PhantomData,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+/// Enough elements to span several `PinVec` buffers.
+const N: usize = if cfg!(miri) { 150 } else { 1000 };
+
+/// Frame which mutably borrows a counter.
+#[derive(Debug)]
+struct Frame<'a>(&'a mut u32);
+
+impl<'a> LtDisable<Frame<'static>> for Frame<'a> {
+    unsafe fn into_static(self) -> Frame<'static> {
+        std::mem::transmute(self)
+    }
+}
+
+impl<'o> LtEnable<'o> for Frame<'static> {
+    type Output = Frame<'o>;
+    
+    unsafe fn give_lifetime(self) -> Frame<'o> { self }
+    
+    unsafe fn give_lifetime_ref(&self) -> &Frame<'o> { self }
+    
+    unsafe fn give_lifetime_mut(&mut self) -> &mut Frame<'o> {
+        std::mem::transmute(self)
+    }
+}
+
+/// Zero-sized frame with a lifetime.
+struct Zst<'a>(PhantomData<&'a mut u32>);
+
+impl<'a> LtDisable<Zst<'static>> for Zst<'a> {
+    unsafe fn into_static(self) -> Zst<'static> { Zst(PhantomData) }
+}
+
+impl<'o> LtEnable<'o> for Zst<'static> {
+    type Output = Zst<'o>;
+    
+    unsafe fn give_lifetime(self) -> Zst<'o> { self }
+    
+    unsafe fn give_lifetime_ref(&self) -> &Zst<'o> { self }
+    
+    unsafe fn give_lifetime_mut(&mut self) -> &mut Zst<'o> {
+        std::mem::transmute(self)
+    }
+}
+
+/// Reborrows the top frame, optionally panicking after having
+/// yielded the reborrow.
+struct Reborrow {
+    panic: bool,
+}
+
+impl<'l> Borrower<'l, Frame<'static>> for Reborrow {
+    type Borrowed = Frame<'l>;
+    type Iterator = Box<dyn Iterator<Item=Frame<'l>> + 'l>;
+    
+    fn apply(self, top: &'l mut Frame<'l>) -> Self::Iterator {
+        let panic = self.panic;
+        let first = Some(Frame(&mut *top.0));
+        Box::new(first.into_iter().chain((0..1)
+            .filter(move |_| panic)
+            .map(|_| panic!("panic in Borrower iterator"))))
+    }
+}
+
+/// Reborrows the top frame, optionally failing in `try_apply`
+/// or after having yielded the reborrow.
+struct TryReborrow {
+    fail_apply: bool,
+    fail_elem: bool,
+}
+
+impl<'l> TryBorrower<'l, Frame<'static>, ()> for TryReborrow {
+    type Borrowed = Frame<'l>;
+    type Iterator = Vec<Result<Frame<'l>, ()>>;
+    
+    fn try_apply(self, top: &'l mut Frame<'l>) -> Result<Self::Iterator, ()> {
+        if self.fail_apply { return Err(()); }
+        let mut elems = vec![Ok(Frame(&mut *top.0))];
+        if self.fail_elem { elems.push(Err(())); }
+        Ok(elems)
+    }
+}
+
+#[test]
+fn push_grow_pop_interleaved() {
+    let mut n: u32 = 0;
+    let mut expected = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Frame(&mut n));
+    
+    for i in 0..N {
+        if i % 3 == 2 {
+            let Frame(r) = stack.pop().unwrap();
+            *r += 1;
+            expected += 1;
+        } else {
+            stack.grow(Reborrow { panic: false });
+        }
+        *stack.top().unwrap().0 += 1;
+        expected += 1;
+    }
+    while let Some(Frame(r)) = stack.pop() {
+        *r += 1;
+        expected += 1;
+    }
+    assert_eq!(n, expected);
+}
+
+#[test]
+fn grow_with_interleaved() {
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Frame(&mut n));
+    
+    for i in 0..N {
+        if i % 4 == 3 {
+            stack.pop_to(stack.len() / 2 + 1);
+        } else {
+            stack.grow_with(|top, frames| frames.push(Frame(top.0)));
+        }
+        *stack.top().unwrap().0 += 1;
+    }
+    drop(stack);
+    assert_eq!(n as usize, N);
+}
+
+/// Interleaves reads of the stack with writes through the top
+/// frame. Reading a lower frame while a frame above reborrows it
+/// would invalidate the reborrow, so `get` is unsafe, and lower
+/// frames are only read once the frames above are popped.
+#[test]
+fn read_lower_write_top() {
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Frame(&mut n));
+    
+    for i in 0..N / 10 {
+        stack.grow_with(|top, frames| frames.push(Frame(top.0)));
+        *stack.top().unwrap().0 += 1;
+        // the top frame has nothing above it
+        let top = unsafe { stack.get(stack.len() - 1) }.unwrap();
+        assert_eq!(*top.0 as usize, i + 1);
+        *stack.top().unwrap().0 += 1;
+        stack.pop();
+        // the frame which reborrowed this one is gone
+        let lower = unsafe { stack.get(stack.len() - 1) }.unwrap();
+        assert_eq!(*lower.0 as usize, i + 2);
+        *stack.top().unwrap().0 -= 1;
+    }
+    drop(stack);
+    assert_eq!(n as usize, N / 10);
+}
+
+#[test]
+fn try_grow_interleaved() {
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Frame(&mut n));
+    
+    for _ in 0..N / 10 {
+        let ok = TryReborrow { fail_apply: false, fail_elem: false };
+        assert_eq!(stack.try_grow(ok), Ok(()));
+        *stack.top().unwrap().0 += 1;
+        
+        let len = stack.len();
+        let fail_apply = TryReborrow { fail_apply: true, fail_elem: false };
+        assert_eq!(stack.try_grow(fail_apply), Err(GrowError::Apply(())));
+        assert_eq!(stack.len(), len);
+        *stack.top().unwrap().0 += 1;
+        
+        let fail_elem = TryReborrow { fail_apply: false, fail_elem: true };
+        assert_eq!(
+            stack.try_grow(fail_elem),
+            Err(GrowError::Elem { pushed: 1, error: () }));
+        assert_eq!(stack.len(), len + 1);
+        *stack.top().unwrap().0 += 1;
+        stack.pop_to(len / 2 + 1);
+    }
+    drop(stack);
+    assert_eq!(n as usize, N / 10 * 3);
+}
+
+#[test]
+fn panic_in_apply() {
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Frame(&mut n));
+    
+    for _ in 0..3 {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            stack.grow(Reborrow { panic: true });
+        }));
+        assert!(result.is_err());
+        
+        // stack remains usable
+        *stack.top().unwrap().0 += 1;
+        stack.grow(Reborrow { panic: false });
+        *stack.top().unwrap().0 += 1;
+    }
+    stack.pop_to(0);
+    assert_eq!(n, 6);
+}
+
+#[test]
+fn drop_nonempty() {
+    let rc = Rc::new(());
+    let mut stack = LtStack::empty();
+    for _ in 0..N {
+        stack.push(Static(Rc::clone(&rc)));
+    }
+    assert_eq!(Rc::strong_count(&rc), N + 1);
+    drop(stack);
+    assert_eq!(Rc::strong_count(&rc), 1);
+    
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+    stack.push(Frame(&mut n));
+    for _ in 0..N {
+        stack.grow_with(|top, frames| frames.push(Frame(top.0)));
+    }
+    drop(stack);
+    n += 1;
+    assert_eq!(n, 1);
+}
+
+#[test]
+fn drop_counts() {
+    let log = DropLog::new();
+    let mut stack = LtStack::empty();
+    log.fill(&mut stack, 0..N, |stack, elem| stack.push(Static(elem)));
+    stack.pop_to(N / 2);
+    assert_eq!(log.len(), N - N / 2);
+    stack.pop();
+    assert_eq!(log.len(), N - N / 2 + 1);
+    drop(stack);
+    assert_eq!(log.len(), N);
+}
+
+#[test]
+fn zero_sized() {
+    let mut stack = LtStack::empty();
+    for _ in 0..N {
+        stack.push(Static(()));
+    }
+    // no element borrows from another
+    assert_eq!(unsafe { stack.iter() }.count(), N);
+    stack.pop_to(1);
+    assert_eq!(stack.pop(), Some(Static(())));
+    assert_eq!(stack.pop(), None);
+    
+    let mut stack = LtStack::empty();
+    stack.push(Zst(PhantomData));
+    for _ in 0..N {
+        stack.grow_with(|_, frames| frames.push(Zst(PhantomData)));
+    }
+    assert_eq!(stack.len(), N + 1);
+    while stack.pop().is_some() {}
+    assert!(stack.is_empty());
+}
@@ -0,0 +1,302 @@
+//! # ltstack_derive
+//!
+//! Derive macro for `ltstack`'s `LtDisable` and `LtEnable` traits.
+
+extern crate proc_macro;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parse_macro_input,
+    spanned::Spanned,
+    punctuated::Punctuated,
+    visit::{self, Visit},
+    Data, DeriveInput, Error, GenericParam, Lifetime,
+    ReturnType, Token, Type, TypeParamBound,
+};
+
+/// Derive `LtDisable<Self<'static>>` for `Self<'a>` and
+/// `LtEnable<'a>` for `Self<'static>`.
+///
+/// The type must be a struct or enum with exactly one lifetime
+/// parameter, and must be covariant in that lifetime. Type
+/// parameters are required to be `'static`.
+///
+/// ```
+/// use ltstack_derive::LtStackElem;
+///
+/// #[derive(LtStackElem)]
+/// struct Foo<'a>(&'a mut u32);
+/// ```
+///
+/// Lifetimes in invariant positions are rejected:
+///
+/// ```compile_fail
+/// use ltstack_derive::LtStackElem;
+/// use std::cell::Cell;
+///
+/// #[derive(LtStackElem)]
+/// struct Foo<'a>(Cell<&'a u32>);
+/// ```
+///
+/// As are lifetimes in positions the derive cannot see through,
+/// but which the compiler finds to be non-covariant:
+///
+/// ```compile_fail
+/// use ltstack_derive::LtStackElem;
+///
+/// struct Invariant<'a>(fn(&'a ()) -> &'a ());
+///
+/// #[derive(LtStackElem)]
+/// struct Foo<'a>(Invariant<'a>);
+/// ```
+#[proc_macro_derive(LtStackElem)]
+pub fn derive_lt_stack_elem(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    // validate shape
+    if let Data::Union(ref data) = input.data {
+        return Err(Error::new(
+            data.union_token.span,
+            "LtStackElem cannot be derived for unions"));
+    }
+    let lifetimes: Vec<Lifetime> = input.generics.lifetimes()
+        .map(|param| param.lifetime.clone())
+        .collect();
+    let lt = match lifetimes.as_slice() {
+        [lt] => lt.clone(),
+        _ => return Err(Error::new(
+            input.generics.span(),
+            "LtStackElem requires exactly one lifetime parameter")),
+    };
+    check_variance(&input.data, &lt)?;
+
+    // type parameters must outlive the erased lifetime
+    for param in input.generics.type_params_mut() {
+        param.bounds.push(TypeParamBound::Lifetime(
+            Lifetime::new("'static", Span::call_site())));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+
+    // `Self` with the lifetime replaced by `'static`
+    let static_ty = {
+        let args = input.generics.params.iter()
+            .map(|param| match param {
+                GenericParam::Lifetime(_) => quote! { 'static },
+                GenericParam::Type(p) => {
+                    let ident = &p.ident;
+                    quote! { #ident }
+                },
+                GenericParam::Const(p) => {
+                    let ident = &p.ident;
+                    quote! { #ident }
+                },
+            });
+        quote! { #name < #( #args ),* > }
+    };
+
+    // giving back the lifetime by value or shared reference is
+    // plain subtyping, so the compiler checks covariance for
+    // whatever check_variance could not see through
+    Ok(quote! {
+        impl #impl_generics ::ltstack::LtDisable<#static_ty>
+        for #name #ty_generics #where_clause {
+            unsafe fn into_static(self) -> #static_ty {
+                let this = ::core::mem::ManuallyDrop::new(self);
+                ::core::ptr::read(
+                    &*this as *const Self as *const #static_ty)
+            }
+        }
+
+        impl #impl_generics ::ltstack::LtEnable<#lt>
+        for #static_ty #where_clause {
+            type Output = #name #ty_generics;
+
+            unsafe fn give_lifetime(self) -> Self::Output { self }
+
+            unsafe fn give_lifetime_ref(&self) -> &Self::Output { self }
+
+            unsafe fn give_lifetime_mut(&mut self) -> &mut Self::Output {
+                &mut *(self as *mut Self as *mut Self::Output)
+            }
+        }
+    })
+}
+
+/// Position of a type relative to the top-level field type.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Variance {
+    Covariant,
+    Contravariant,
+    Invariant,
+}
+
+impl Variance {
+    fn flip(self) -> Self {
+        match self {
+            Variance::Covariant => Variance::Contravariant,
+            Variance::Contravariant => Variance::Covariant,
+            Variance::Invariant => Variance::Invariant,
+        }
+    }
+}
+
+/// Type constructors known to be invariant in their parameters.
+const INVARIANT_WRAPPERS: &[&str] = &[
+    "Cell", "RefCell", "UnsafeCell", "OnceCell",
+    "Mutex", "RwLock", "OnceLock",
+];
+
+/// Find uses of the lifetime in positions which are not covariant.
+///
+/// This is only syntactic, and only exists to provide better
+/// errors. Soundness relies on the generated `give_lifetime`.
+struct VarianceCheck<'l> {
+    lt: &'l Lifetime,
+    variance: Variance,
+    error: Option<Error>,
+}
+
+impl<'l> VarianceCheck<'l> {
+    fn visit_with<F>(&mut self, variance: Variance, f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let old = self.variance;
+        self.variance = variance;
+        f(self);
+        self.variance = old;
+    }
+
+    fn report(&mut self, lt: &Lifetime) {
+        let msg = match self.variance {
+            Variance::Covariant => return,
+            Variance::Contravariant => "contravariant",
+            Variance::Invariant => "invariant",
+        };
+        let error = Error::new(lt.span(), format!(
+            "lifetime `{}` appears in {} position, LtStackElem \
+            requires the type to be covariant in it", lt, msg));
+        match self.error {
+            Some(ref mut e) => e.combine(error),
+            None => self.error = Some(error),
+        }
+    }
+}
+
+impl<'l, 'ast> Visit<'ast> for VarianceCheck<'l> {
+    fn visit_lifetime(&mut self, lt: &'ast Lifetime) {
+        if lt == self.lt {
+            self.report(lt);
+        }
+    }
+
+    fn visit_type(&mut self, ty: &'ast Type) {
+        match ty {
+            Type::Reference(r) => {
+                if let Some(ref lt) = r.lifetime {
+                    self.visit_lifetime(lt);
+                }
+                let inner = if r.mutability.is_some() {
+                    Variance::Invariant
+                } else {
+                    self.variance
+                };
+                self.visit_with(inner, |s| s.visit_type(&r.elem));
+            },
+            Type::Ptr(p) => {
+                let inner = if p.mutability.is_some() {
+                    Variance::Invariant
+                } else {
+                    self.variance
+                };
+                self.visit_with(inner, |s| s.visit_type(&p.elem));
+            },
+            Type::BareFn(f) => {
+                let outer = self.variance;
+                self.visit_with(outer.flip(), |s| for input in &f.inputs {
+                    s.visit_type(&input.ty);
+                });
+                if let ReturnType::Type(_, ref output) = f.output {
+                    self.visit_type(output);
+                }
+            },
+            Type::TraitObject(t) => {
+                self.visit_bounds(&t.bounds);
+            },
+            Type::ImplTrait(t) => {
+                self.visit_bounds(&t.bounds);
+            },
+            Type::Path(p) => {
+                if let Some(ref qself) = p.qself {
+                    // projections are invariant
+                    self.visit_with(Variance::Invariant, |s| {
+                        s.visit_type(&qself.ty);
+                        s.visit_path(&p.path);
+                    });
+                    return;
+                }
+                for seg in &p.path.segments {
+                    let variance =
+                        if INVARIANT_WRAPPERS.iter()
+                            .any(|w| seg.ident == w)
+                        {
+                            Variance::Invariant
+                        } else {
+                            self.variance
+                        };
+                    self.visit_with(variance, |s|
+                        s.visit_path_arguments(&seg.arguments));
+                }
+            },
+            _ => visit::visit_type(self, ty),
+        }
+    }
+}
+
+impl<'l> VarianceCheck<'l> {
+    fn visit_bounds(&mut self, bounds: &Punctuated<TypeParamBound, Token![+]>) {
+        for bound in bounds {
+            match bound {
+                TypeParamBound::Lifetime(lt) => self.visit_lifetime(lt),
+                TypeParamBound::Trait(t) => {
+                    // trait parameters are invariant
+                    self.visit_with(Variance::Invariant, |s|
+                        s.visit_trait_bound(t));
+                },
+                _ => (),
+            }
+        }
+    }
+}
+
+fn check_variance(data: &Data, lt: &Lifetime) -> Result<(), Error> {
+    let mut check = VarianceCheck {
+        lt,
+        variance: Variance::Covariant,
+        error: None,
+    };
+    match data {
+        Data::Struct(data) => for field in &data.fields {
+            check.visit_type(&field.ty);
+        },
+        Data::Enum(data) => for variant in &data.variants {
+            for field in &variant.fields {
+                check.visit_type(&field.ty);
+            }
+        },
+        Data::Union(_) => unreachable!(),
+    }
+    match check.error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
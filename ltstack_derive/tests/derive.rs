@@ -0,0 +1,85 @@
+use ltstack::{LtStack, Borrower};
+use ltstack_derive::LtStackElem;
+
+#[derive(Debug, LtStackElem)]
+struct Foo<'a>(&'a mut u32);
+
+#[derive(Debug, LtStackElem)]
+enum Bar<'a, T> {
+    Owned(T),
+    Borrowed(&'a mut T),
+}
+
+#[derive(Debug, LtStackElem)]
+struct Baz<'b, T: Clone, const N: usize>
+where
+    T: Default,
+{
+    elems: [&'b T; N],
+}
+
+#[test]
+fn derived_struct() {
+    let mut n: u32 = 0;
+    let mut stack = LtStack::empty();
+
+    struct B;
+    impl<'l> Borrower<'l, Foo<'static>> for B {
+        type Borrowed = Foo<'l>;
+        type Iterator = Option<Foo<'l>>;
+
+        fn apply(self, top: &'l mut Foo<'l>) -> Option<Foo<'l>> {
+            Some(Foo(top.0))
+        }
+    }
+
+    stack.push(Foo(&mut n));
+    for _ in 0..9 {
+        stack.grow(B);
+    }
+    while let Some(Foo(r)) = stack.pop() {
+        *r += 1;
+    }
+    assert_eq!(n, 10);
+}
+
+#[test]
+fn derived_enum() {
+    let mut stack = LtStack::empty();
+
+    struct B;
+    impl<'l> Borrower<'l, Bar<'static, String>> for B {
+        type Borrowed = Bar<'l, String>;
+        type Iterator = Option<Bar<'l, String>>;
+
+        fn apply(self, top: &'l mut Bar<'l, String>) -> Self::Iterator {
+            match top {
+                Bar::Owned(s) => Some(Bar::Borrowed(s)),
+                Bar::Borrowed(_) => None,
+            }
+        }
+    }
+
+    stack.push(Bar::Owned("hello".to_owned()));
+    stack.grow(B);
+    stack.grow(B);
+    assert_eq!(stack.len(), 2);
+    match stack.top() {
+        Some(Bar::Borrowed(s)) => s.push_str(" world"),
+        _ => panic!("expected borrowed top"),
+    }
+    stack.pop();
+    match stack.pop() {
+        Some(Bar::Owned(s)) => assert_eq!(s, "hello world"),
+        _ => panic!("expected owned bottom"),
+    }
+}
+
+#[test]
+fn derived_generics() {
+    let (a, b) = (1u8, 2u8);
+    let mut stack: LtStack<Baz<'static, u8, 2>> = LtStack::empty();
+    stack.push(Baz { elems: [&a, &b] });
+    let top = stack.top().unwrap();
+    assert_eq!(*top.elems[0] + *top.elems[1], 3);
+}
@@ -0,0 +1,58 @@
+//! Compare `PinVec` indexing against `Vec` and `VecDeque`.
+//!
+//!     cargo bench -p pinvec --bench index
+
+use std::collections::VecDeque;
+use criterion::{
+    criterion_group, criterion_main, black_box, 
+    Criterion, BenchmarkId,
+};
+use pinvec::PinVec;
+use pow_of_2::PowOf2;
+
+const LENS: &[usize] = &[1 << 10, 1 << 16, 1 << 20];
+
+/// Deterministic pseudo-random indices below `len`.
+fn random_indices(len: usize) -> Vec<usize> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % len as u64) as usize
+        })
+        .collect()
+}
+
+fn bench_index(c: &mut Criterion, name: &str, indices: fn(usize) -> Vec<usize>) {
+    let mut group = c.benchmark_group(name);
+    for &len in LENS {
+        let indices = indices(len);
+        
+        let mut pin_vec = PinVec::new(PowOf2::<usize>::_64);
+        for i in 0..len { pin_vec.push(i); }
+        group.bench_with_input(BenchmarkId::new("PinVec", len), &indices, |b, indices| 
+            b.iter(|| indices.iter().map(|&i| pin_vec[black_box(i)]).sum::<usize>()));
+        
+        let vec: Vec<usize> = (0..len).collect();
+        group.bench_with_input(BenchmarkId::new("Vec", len), &indices, |b, indices| 
+            b.iter(|| indices.iter().map(|&i| vec[black_box(i)]).sum::<usize>()));
+        
+        let deque: VecDeque<usize> = (0..len).collect();
+        group.bench_with_input(BenchmarkId::new("VecDeque", len), &indices, |b, indices| 
+            b.iter(|| indices.iter().map(|&i| deque[black_box(i)]).sum::<usize>()));
+    }
+    group.finish();
+}
+
+fn sequential(c: &mut Criterion) {
+    bench_index(c, "index_sequential", |len| (0..len.min(1024)).collect());
+}
+
+fn random(c: &mut Criterion) {
+    bench_index(c, "index_random", random_indices);
+}
+
+criterion_group!(benches, sequential, random);
+criterion_main!(benches);
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests;
+
+use crate::vec::PinVec;
+use core::{
+    pin::Pin,
+    cell::UnsafeCell,
+};
+use pow_of_2::PowOf2;
+
+/// Typed arena with stable memory addresses.
+///
+/// Elements are allocated through a shared reference, and live
+/// as long as the arena. As elements never move, references to 
+/// them can be stored in other elements, forming cyclic graphs 
+/// without `Rc`.
+pub struct PinArena<T> {
+    vec: UnsafeCell<PinVec<T>>,
+}
+
+impl<T> PinArena<T> {
+    /// New, empty `PinArena`.
+    pub fn new(buf_0_len: PowOf2<usize>) -> Self {
+        PinArena { vec: UnsafeCell::new(PinVec::new(buf_0_len)) }
+    }
+    
+    /// Current length in elements.
+    pub fn len(&self) -> usize {
+        unsafe { (*self.vec.get()).len() }
+    }
+    
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    
+    /// Allocate an element, returning a pinned mutable ref.
+    ///
+    /// Allocates more memory if necessary, but never moves
+    /// existing elements.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_mut(&self, elem: T) -> Pin<&mut T> {
+        unsafe {
+            // no reference into the PinVec itself outlives this
+            // call, only references to its elements, which are not
+            // touched by pushing. push_with only borrows the new
+            // slot, whereas going through last_mut would borrow the
+            // whole top buffer, invalidating existing references.
+            let vec = &mut *self.vec.get();
+            let top = vec.push_with(|slot| slot.map_unchecked_mut(|slot| slot.write(elem)));
+            Pin::new_unchecked(&mut *(Pin::get_unchecked_mut(top) as *mut T))
+        }
+    }
+    
+    /// Allocate an element, returning a pinned shared ref.
+    pub fn alloc(&self, elem: T) -> Pin<&T> {
+        self.alloc_mut(elem).into_ref()
+    }
+    
+    /// Give access to all elements, as a `PinVec`.
+    pub fn vec_mut(&mut self) -> &mut PinVec<T> {
+        self.vec.get_mut()
+    }
+    
+    /// Convert into a `PinVec`, without moving any elements.
+    pub fn into_vec(self) -> PinVec<T> {
+        self.vec.into_inner()
+    }
+}
+
+impl<T> From<PinVec<T>> for PinArena<T> {
+    fn from(vec: PinVec<T>) -> Self {
+        PinArena { vec: UnsafeCell::new(vec) }
+    }
+}
+
+impl<T> Default for PinArena<T> {
+    fn default() -> Self {
+        PinArena::from(PinVec::default())
+    }
+}
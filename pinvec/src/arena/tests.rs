@@ -0,0 +1,56 @@
+use crate::PinArena;
+use std::cell::Cell;
+use pow_of_2::PowOf2;
+
+#[test]
+fn alloc_addrstable() {
+    for buf_0_len in (0..10).map(PowOf2::<usize>::from_exp) {
+        let mut arena: PinArena<usize> = PinArena::new(buf_0_len);
+        let elems: Vec<&usize> = (0..1000)
+            .map(|i| arena.alloc(i).get_ref())
+            .collect();
+        assert_eq!(arena.len(), 1000);
+        for (i, &elem) in elems.iter().enumerate() {
+            assert_eq!(*elem, i);
+        }
+        
+        let addrs: Vec<*const usize> = elems.into_iter()
+            .map(|elem| elem as *const usize)
+            .collect();
+        for (elem, addr) in arena.vec_mut().iter().zip(addrs) {
+            assert_eq!(&*elem as *const usize, addr);
+        }
+    }
+}
+
+#[test]
+fn alloc_mut() {
+    let arena = PinArena::default();
+    let a = arena.alloc_mut(1u32).get_mut();
+    let b = arena.alloc_mut(2u32).get_mut();
+    *a += 10;
+    *b += 20;
+    assert_eq!(arena.into_vec().iter().map(|r| *r).collect::<Vec<_>>(), [11, 22]);
+}
+
+struct Node<'a> {
+    val: u32,
+    next: Cell<Option<&'a Node<'a>>>,
+}
+
+#[test]
+fn cyclic_graph() {
+    let arena = PinArena::new(PowOf2::<usize>::_1);
+    let nodes: Vec<&Node> = (0..100)
+        .map(|val| arena.alloc(Node { val, next: Cell::new(None) }).get_ref())
+        .collect();
+    for (i, node) in nodes.iter().enumerate() {
+        node.next.set(Some(nodes[(i + 1) % nodes.len()]));
+    }
+    
+    let mut node = nodes[0];
+    for i in 0..250 {
+        assert_eq!(node.val, i % 100);
+        node = node.next.get().unwrap();
+    }
+}
@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod tests;
+
+use crate::buf::{Iter, IterMut, drop_rev};
+use core::{
+    pin::Pin,
+    marker::Unpin,
+    mem::{replace, MaybeUninit},
+    ops::{Index, IndexMut},
+    ptr,
+    slice,
+};
+
+/// Fixed-capacity buffer which stores its elements inline.
+///
+/// Elements are dropped top-first.
+///
+/// Unlike a `PinBuffer`, moving this moves its elements, so
+/// methods which hand out pinned references require the buffer
+/// itself to be pinned. The checked getters are named 
+/// `try_idx_ref` and `try_idx_mut`, as `get_ref` and `get_mut`
+/// would be shadowed by `Pin`'s own methods.
+pub struct PinArrayBuffer<T, const N: usize> {
+    elems: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T, const N: usize> PinArrayBuffer<T, N> {
+    /// New, empty buffer.
+    pub const fn new() -> Self {
+        PinArrayBuffer {
+            elems: [const { MaybeUninit::uninit() }; N],
+            len: 0,
+        }
+    }
+    
+    /// Current element length.
+    pub fn len(&self) -> usize { self.len }
+    
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    
+    /// Element capacity.
+    pub fn capacity(&self) -> usize { N }
+    
+    /// Whether capacity allows to push another element.
+    pub fn can_push(&self) -> bool {
+        self.len < N
+    }
+    
+    fn as_slice(&self) -> &[T] {
+        unsafe {
+            slice::from_raw_parts(self.elems.as_ptr() as *const T, self.len)
+        }
+    }
+    
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe {
+            slice::from_raw_parts_mut(self.elems.as_mut_ptr() as *mut T, self.len)
+        }
+    }
+    
+    /// Push an element. Panics if at capacity.
+    pub fn push(self: Pin<&mut Self>, elem: T) {
+        assert!(self.can_push(), "push to full PinArrayBuffer");
+        let this = unsafe { self.get_unchecked_mut() };
+        this.elems[this.len].write(elem);
+        this.len += 1;
+    }
+    
+    /// Pop and drop the top element.
+    ///
+    /// Return false if already empty.
+    ///
+    /// Returning element would violate `Pin` variants.
+    pub fn remove_top(self: Pin<&mut Self>) -> bool {
+        let this = unsafe { self.get_unchecked_mut() };
+        if this.len == 0 { return false; }
+        this.len -= 1;
+        unsafe {
+            // take special care to drop element without moving it
+            ptr::drop_in_place(this.elems[this.len].as_mut_ptr());
+        }
+        true
+    }
+    
+    /// Pop and return the top element.
+    ///
+    /// Only possible if the element type is `Unpin`.
+    pub fn pop(&mut self) -> Option<T>
+    where T: Unpin {
+        unsafe { Pin::new(self).pop_unchecked() }
+    }
+    
+    /// Pop and return the top element, ignoring `Pin`
+    /// invariants.
+    ///
+    /// # Safety
+    ///
+    /// The caller must uphold the `Pin` contract for the
+    /// moved-out element themselves.
+    pub unsafe fn pop_unchecked(self: Pin<&mut Self>) -> Option<T> {
+        let this = self.get_unchecked_mut();
+        if this.len == 0 { return None; }
+        this.len -= 1;
+        Some(this.elems[this.len].as_ptr().read())
+    }
+    
+    /// Override an existing element.
+    ///
+    /// Quoting [std::pin::Pin](https://doc.rust-lang.org/std/pin/struct.Pin.html#method.set):
+    ///
+    /// > This overwrites pinned data, but that is okay:
+    /// > its destructor gets run before being overwritten,
+    /// > so no pinning guarantee is violated.
+    pub fn set(self: Pin<&mut Self>, index: usize, elem: T) {
+        unsafe { self.get_unchecked_mut() }.as_mut_slice()[index] = elem;
+    }
+    
+    /// Override an existing element, or give it back if the
+    /// index is out of bounds.
+    ///
+    /// See `PinArrayBuffer::set`.
+    pub fn try_set(self: Pin<&mut Self>, index: usize, elem: T) -> Result<(), T> {
+        match unsafe { self.get_unchecked_mut() }.as_mut_slice().get_mut(index) {
+            Some(slot) => {
+                *slot = elem;
+                Ok(())
+            },
+            None => Err(elem),
+        }
+    }
+    
+    /// Take and replace an existing element.
+    ///
+    /// Only possible if the element type is `Unpin`.
+    ///
+    /// Panics on failure.
+    pub fn replace(&mut self, index: usize, repl: T) -> T
+    where T: Unpin {
+        replace(&mut self.as_mut_slice()[index], repl)
+    }
+    
+    /// Get by index as pinned shared ref, or panic.
+    pub fn idx_ref(self: Pin<&Self>, index: usize) -> Pin<&T> {
+        unsafe { self.map_unchecked(|this| &this.as_slice()[index]) }
+    }
+    
+    /// Get by index as pinned mutable ref, or panic.
+    pub fn idx_mut(self: Pin<&mut Self>, index: usize) -> Pin<&mut T> {
+        unsafe { self.map_unchecked_mut(|this| &mut this.as_mut_slice()[index]) }
+    }
+    
+    /// Get by index as pinned shared ref.
+    pub fn try_idx_ref(self: Pin<&Self>, index: usize) -> Option<Pin<&T>> {
+        let this = Pin::get_ref(self);
+        this.as_slice().get(index)
+            .map(|r| unsafe { Pin::new_unchecked(r) })
+    }
+    
+    /// Get by index as pinned mutable ref.
+    pub fn try_idx_mut(self: Pin<&mut Self>, index: usize) -> Option<Pin<&mut T>> {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.as_mut_slice().get_mut(index)
+            .map(|r| unsafe { Pin::new_unchecked(r) })
+    }
+    
+    /// Iterate as pinned shared refs.
+    pub fn iter(self: Pin<&Self>) -> Iter<'_, T> {
+        Iter(Pin::get_ref(self).as_slice().iter())
+    }
+    
+    /// Iterate as pinned mutable refs.
+    pub fn iter_mut(self: Pin<&mut Self>) -> IterMut<'_, T> {
+        IterMut(unsafe { self.get_unchecked_mut() }.as_mut_slice().iter_mut())
+    }
+}
+
+impl<T, const N: usize> Drop for PinArrayBuffer<T, N> {
+    fn drop(&mut self) {
+        unsafe { drop_rev(self.elems.as_mut_ptr() as *mut T, self.len); }
+    }
+}
+
+impl<T, const N: usize> Default for PinArrayBuffer<T, N> {
+    fn default() -> Self {
+        PinArrayBuffer::new()
+    }
+}
+
+impl<T, const N: usize> Index<usize> for PinArrayBuffer<T, N> {
+    type Output = T;
+    
+    fn index(&self, index: usize) -> &T {
+        &self.as_slice()[index]
+    }
+}
+
+/// Only possible if the element type is `Unpin`.
+impl<T: Unpin, const N: usize> IndexMut<usize> for PinArrayBuffer<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.as_mut_slice()[index]
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{
+    PinArrayBuffer,
+    test_util::DropLog,
+};
+use std::{
+    pin::Pin,
+    marker::PhantomPinned,
+};
+
+#[test]
+fn push_set_remove() {
+    let mut buf: Pin<Box<PinArrayBuffer<usize, 16>>> = Box::pin(PinArrayBuffer::new());
+    assert_eq!(buf.capacity(), 16);
+    for i in 0..16 {
+        assert!(buf.can_push());
+        buf.as_mut().push(i);
+    }
+    assert!(!buf.can_push());
+    assert_eq!(buf.len(), 16);
+    assert!(buf.as_ref().iter().map(|r| *r).eq(0..16));
+    
+    buf.as_mut().set(3, 30);
+    assert_eq!(*buf.as_ref().idx_ref(3), 30);
+    *buf.as_mut().idx_mut(4) = 40;
+    assert_eq!(buf[4], 40);
+    assert_eq!(buf.as_mut().try_set(16, 160), Err(160));
+    assert!(buf.as_ref().try_idx_ref(16).is_none());
+    
+    assert!(buf.as_mut().remove_top());
+    assert_eq!(Pin::get_mut(buf.as_mut()).pop(), Some(14));
+    assert_eq!(buf.len(), 14);
+}
+
+#[test]
+#[should_panic]
+fn push_full() {
+    let mut buf: PinArrayBuffer<u8, 2> = PinArrayBuffer::new();
+    let mut buf = Pin::new(&mut buf);
+    for i in 0..3 {
+        buf.as_mut().push(i);
+    }
+}
+
+#[test]
+fn addrstable() {
+    struct Elem(usize, PhantomPinned);
+    
+    let mut buf: Pin<Box<PinArrayBuffer<Elem, 64>>> = Box::pin(PinArrayBuffer::new());
+    let mut addrs = Vec::new();
+    for i in 0..64 {
+        buf.as_mut().push(Elem(i, PhantomPinned));
+        addrs.push(&*buf.as_ref().idx_ref(i) as *const Elem);
+    }
+    for i in 0..64 {
+        buf.as_mut().remove_top();
+        let elem = buf.as_ref();
+        for (j, elem) in elem.iter().enumerate() {
+            assert_eq!(elem.0, j);
+            assert_eq!(&*elem as *const Elem, addrs[j]);
+        }
+        assert_eq!(buf.len(), 63 - i);
+    }
+}
+
+#[test]
+fn drop_elems() {
+    let log = DropLog::new();
+    let mut buf: Pin<Box<PinArrayBuffer<_, 8>>> = Box::pin(PinArrayBuffer::new());
+    log.fill(&mut buf, 0..5, |buf, elem| buf.as_mut().push(elem));
+    buf.as_mut().set(0, log.elem(5));
+    assert_eq!(log.vals(), [0]);
+    buf.as_mut().remove_top();
+    assert_eq!(log.vals(), [0, 4]);
+    drop(buf);
+    assert_eq!(log.vals(), [0, 4, 3, 2, 1, 5]);
+}
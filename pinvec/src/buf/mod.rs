@@ -1,26 +1,157 @@
+#[cfg(test)]
+mod tests;
 
-use std::{
+use core::{
     pin::Pin,
-    marker::Unpin,
-    ptr::drop_in_place,
-    mem::replace,
+    marker::{Unpin, PhantomData},
+    ptr::{self, NonNull},
+    mem::{self, replace, ManuallyDrop, MaybeUninit},
+    ops::{Index, IndexMut, RangeFrom, Deref, DerefMut},
+    slice,
 };
+use alloc::vec::Vec;
+use allocator_api2::alloc::{
+    Allocator,
+    Global,
+    Layout,
+    handle_alloc_error,
+};
+
+/// Fixed-capacity buffer which never re-allocates.
+///
+/// Elements are dropped top-first.
+#[repr(transparent)]
+pub struct PinBuffer<T, A: Allocator = Global> {
+    raw: RawBuf<A>,
+    _p: PhantomData<T>,
+}
+
+/// Allocation backing a `PinBuffer`, which drops its elements
+/// through a function pointer rather than naming their type.
+///
+/// Keeping `T` out of the type with the `Drop` impl lets the
+/// drop checker treat `PinBuffer<T>` like `Vec<T>`, requiring 
+/// only that `T`'s own drop glue be sound to run.
+struct RawBuf<A: Allocator> {
+    ptr: NonNull<u8>,
+    len: usize,
+    cap: usize,
+    layout: Layout,
+    drop_elems: unsafe fn(NonNull<u8>, usize),
+    alloc: A,
+}
+
+/// Drop the first `len` elements of type `T` at `ptr`.
+unsafe fn drop_elems<T>(ptr: NonNull<u8>, len: usize) {
+    drop_rev(ptr.cast::<T>().as_ptr(), len);
+}
+
+/// Drop `len` elements at `ptr` in place, top-first. 
+///
+/// If dropping an element panics, the elements below it are still
+/// dropped while unwinding.
+pub(crate) unsafe fn drop_rev<T>(ptr: *mut T, len: usize) {
+    struct Rest<T>(*mut T, usize);
+    
+    impl<T> Drop for Rest<T> {
+        fn drop(&mut self) {
+            // only has elements left if unwinding
+            while self.1 > 0 {
+                self.1 -= 1;
+                unsafe { ptr::drop_in_place(self.0.add(self.1)); }
+            }
+        }
+    }
+    
+    let mut rest = Rest(ptr, len);
+    while rest.1 > 0 {
+        rest.1 -= 1;
+        ptr::drop_in_place(rest.0.add(rest.1));
+    }
+}
 
-/// Wraps `Vec<T>` and disallows re-allocation.
-pub struct PinBuffer<T> {
-    vec: Vec<T>
+impl<A: Allocator> Drop for RawBuf<A> {
+    fn drop(&mut self) {
+        // deallocate even if dropping an element panics
+        struct Dealloc<'r, A: Allocator>(&'r RawBuf<A>);
+        
+        impl<'r, A: Allocator> Drop for Dealloc<'r, A> {
+            fn drop(&mut self) {
+                let raw = self.0;
+                if raw.layout.size() != 0 {
+                    unsafe { raw.alloc.deallocate(raw.ptr, raw.layout); }
+                }
+            }
+        }
+        
+        let _dealloc = Dealloc(self);
+        unsafe { (self.drop_elems)(self.ptr, self.len); }
+    }
 }
 
+unsafe impl<T: Send, A: Allocator + Send> Send for PinBuffer<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for PinBuffer<T, A> {}
+
 impl<T> PinBuffer<T> {
     pub fn new(capacity: usize) -> Self {
-        PinBuffer { vec: Vec::with_capacity(capacity) }
+        PinBuffer::new_in(capacity, Global)
+    }
+}
+
+impl<T, A: Allocator> PinBuffer<T, A> {
+    /// New, empty buffer, allocated in `alloc`.
+    pub fn new_in(capacity: usize, alloc: A) -> Self {
+        let layout = Layout::array::<T>(capacity)
+            .expect("capacity overflow");
+        let ptr = 
+            if layout.size() == 0 {
+                NonNull::<T>::dangling().cast()
+            } else {
+                match alloc.allocate(layout) {
+                    Ok(ptr) => ptr.cast(),
+                    Err(_) => handle_alloc_error(layout),
+                }
+            };
+        PinBuffer {
+            raw: RawBuf {
+                ptr,
+                len: 0,
+                cap: capacity,
+                layout,
+                drop_elems: drop_elems::<T>,
+                alloc,
+            },
+            _p: PhantomData,
+        }
+    }
+    
+    /// The allocator the buffer is allocated in.
+    pub fn allocator(&self) -> &A { &self.raw.alloc }
+    
+    pub(crate) fn ptr(&self) -> *mut T { self.raw.ptr.cast::<T>().as_ptr() }
+    
+    /// Set the length without dropping or initializing elements.
+    pub(crate) unsafe fn set_len(&mut self, len: usize) {
+        debug_assert!(len <= self.raw.cap);
+        self.raw.len = len;
+    }
+    
+    fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr(), self.raw.len) }
+    }
+    
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr(), self.raw.len) }
     }
     
     /// Current element length.
-    pub fn len(&self) -> usize { self.vec.len() }
+    pub fn len(&self) -> usize { self.raw.len }
+    
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool { self.raw.len == 0 }
     
     /// Current element capacity.
-    pub fn capacity(&self) -> usize { self.vec.capacity() }
+    pub fn capacity(&self) -> usize { self.raw.cap }
     
     /// Whether capacity allows to push another element.
     pub fn can_push(&self) -> bool {
@@ -30,7 +161,32 @@ impl<T> PinBuffer<T> {
     /// Push an element. Panics if at capacity.
     pub fn push(&mut self, elem: T) {
         assert!(self.can_push(), "push to full PinBuffer");
-        self.vec.push(elem);
+        unsafe { self.ptr().add(self.raw.len).write(elem); }
+        self.raw.len += 1;
+    }
+    
+    /// Push an element by initializing it in place, for types 
+    /// which must know their address when constructed. Panics 
+    /// if at capacity.
+    ///
+    /// `init` must return the slot it was given, initialized.
+    /// Panics if it returns any other reference. If `init` 
+    /// panics, nothing is pushed.
+    pub fn push_with<F>(&mut self, init: F) -> Pin<&mut T>
+    where
+        F: for<'s> FnOnce(Pin<&'s mut MaybeUninit<T>>) -> Pin<&'s mut T>,
+    {
+        assert!(self.can_push(), "push to full PinBuffer");
+        unsafe {
+            let slot = self.ptr().add(self.raw.len);
+            let elem = init(Pin::new_unchecked(
+                &mut *(slot as *mut MaybeUninit<T>)));
+            let elem: *mut T = Pin::get_unchecked_mut(elem);
+            assert!(elem == slot, "push_with init returned \
+                reference to other than its slot");
+            self.raw.len += 1;
+            Pin::new_unchecked(&mut *slot)
+        }
     }
     
     /// Pop and drop the top element. 
@@ -39,15 +195,11 @@ impl<T> PinBuffer<T> {
     /// 
     /// Returning element would violate `Pin` variants.
     pub fn remove_top(&mut self) -> bool {
-        let len = match self.vec.len() {
-            0 => return false,
-            l => l,
-        };
+        if self.raw.len == 0 { return false; }
+        self.raw.len -= 1;
         unsafe {
             // take special care to drop element without moving it
-            let top: *mut T = &mut self.vec[len - 1];
-            self.vec.set_len(len - 1);
-            drop_in_place(top);
+            ptr::drop_in_place(self.ptr().add(self.raw.len));
         }
         true
     }
@@ -57,13 +209,54 @@ impl<T> PinBuffer<T> {
     /// Only possible if the element type is `Unpin`.
     pub fn pop(&mut self) -> Option<T> 
     where T: Unpin {
-        self.vec.pop() 
+        unsafe { self.pop_unchecked() }
+    }
+    
+    /// Drop elements from the top until only `len` remain.
+    ///
+    /// Elements are dropped in place, top-first. Does nothing if
+    /// there are already no more than `len` elements.
+    pub fn truncate(&mut self, len: usize) {
+        while self.raw.len > len {
+            self.raw.len -= 1;
+            unsafe { ptr::drop_in_place(self.ptr().add(self.raw.len)); }
+        }
+    }
+    
+    /// Drop all elements, top-first.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+    
+    /// Remove and iterate over elements from `range.start` to the
+    /// top. Elements not iterated over are dropped top-first when
+    /// the iterator is dropped.
+    ///
+    /// Only possible if the element type is `Unpin`.
+    ///
+    /// Panics if the start is out of bounds.
+    pub fn drain(&mut self, range: RangeFrom<usize>) -> Drain<'_, T, A>
+    where T: Unpin {
+        let len = self.raw.len;
+        assert!(range.start <= len, "drain start {} out of bounds, \
+            buffer length = {}", range.start, len);
+        
+        // elements are now owned by the iterator
+        self.raw.len = range.start;
+        Drain { buf: self, front: range.start, back: len }
     }
     
     /// Pop and return the top element, ignoring `Pin` 
     /// invariants.
+    ///
+    /// # Safety
+    ///
+    /// The caller must uphold the `Pin` contract for the
+    /// moved-out element themselves.
     pub unsafe fn pop_unchecked(&mut self) -> Option<T> {
-        self.vec.pop()
+        if self.raw.len == 0 { return None; }
+        self.raw.len -= 1;
+        Some(self.ptr().add(self.raw.len).read())
     }
     
     /// Override an existing element.
@@ -74,7 +267,21 @@ impl<T> PinBuffer<T> {
     /// > its destructor gets run before being overwritten,
     /// > so no pinning guarantee is violated.
     pub fn set(&mut self, index: usize, elem: T) {
-        self.vec[index] = elem;
+        self.as_mut_slice()[index] = elem;
+    }
+    
+    /// Override an existing element, or give it back if the 
+    /// index is out of bounds.
+    ///
+    /// See `PinBuffer::set`.
+    pub fn try_set(&mut self, index: usize, elem: T) -> Result<(), T> {
+        match self.as_mut_slice().get_mut(index) {
+            Some(slot) => {
+                *slot = elem;
+                Ok(())
+            },
+            None => Err(elem),
+        }
     }
     
     /// Take and replace an existing element.
@@ -84,28 +291,303 @@ impl<T> PinBuffer<T> {
     /// Panics on failure.
     pub fn replace(&mut self, index: usize, repl: T) -> T 
     where T: Unpin {
-        replace(&mut self.vec[index], repl)
+        replace(&mut self.as_mut_slice()[index], repl)
     }
     
     /// Get by index as pinned shared ref, or panic.
     pub fn idx_ref(&self, index: usize) -> Pin<&T> {
-        unsafe { Pin::new_unchecked(&self.vec[index]) }
+        unsafe { Pin::new_unchecked(&self.as_slice()[index]) }
     }
     
     /// Get by index as pinned mutable ref, or panic.
     pub fn idx_mut(&mut self, index: usize) -> Pin<&mut T> {
-        unsafe { Pin::new_unchecked(&mut self.vec[index]) } 
+        unsafe { Pin::new_unchecked(&mut self.as_mut_slice()[index]) } 
     }
     
     /// Get by index as pinned shared ref.
     pub fn get_ref(&self, index: usize) -> Option<Pin<&T>> {
-        self.vec.get(index)
+        self.as_slice().get(index)
             .map(|r| unsafe { Pin::new_unchecked(r) })
     }
     
     /// Get by index as pinned mutable ref.
     pub fn get_mut(&mut self, index: usize) -> Option<Pin<&mut T>> {
-        self.vec.get_mut(index)
+        self.as_mut_slice().get_mut(index)
+            .map(|r| unsafe { Pin::new_unchecked(r) })
+    }
+    
+    /// Get the bottom element as pinned shared ref.
+    pub fn first_ref(&self) -> Option<Pin<&T>> {
+        self.as_slice().first()
+            .map(|r| unsafe { Pin::new_unchecked(r) })
+    }
+    
+    /// Get the bottom element as pinned mutable ref.
+    pub fn first_mut(&mut self) -> Option<Pin<&mut T>> {
+        self.as_mut_slice().first_mut()
             .map(|r| unsafe { Pin::new_unchecked(r) })
     }
-}
\ No newline at end of file
+    
+    /// Get the top element as pinned shared ref.
+    pub fn last_ref(&self) -> Option<Pin<&T>> {
+        self.as_slice().last()
+            .map(|r| unsafe { Pin::new_unchecked(r) })
+    }
+    
+    /// Get the top element as pinned mutable ref.
+    pub fn last_mut(&mut self) -> Option<Pin<&mut T>> {
+        self.as_mut_slice().last_mut()
+            .map(|r| unsafe { Pin::new_unchecked(r) })
+    }
+    
+    /// Iterate as pinned shared refs.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter(self.as_slice().iter())
+    }
+    
+    /// Iterate as pinned mutable refs.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut(self.as_mut_slice().iter_mut())
+    }
+}
+
+impl<T, A: Allocator> Index<usize> for PinBuffer<T, A> {
+    type Output = T;
+    
+    fn index(&self, index: usize) -> &T {
+        &self.as_slice()[index]
+    }
+}
+
+/// Only possible if the element type is `Unpin`.
+impl<T: Unpin, A: Allocator> IndexMut<usize> for PinBuffer<T, A> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.as_mut_slice()[index]
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a PinBuffer<T, A> {
+    type Item = Pin<&'a T>;
+    type IntoIter = Iter<'a, T>;
+    
+    fn into_iter(self) -> Iter<'a, T> { self.iter() }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut PinBuffer<T, A> {
+    type Item = Pin<&'a mut T>;
+    type IntoIter = IterMut<'a, T>;
+    
+    fn into_iter(self) -> IterMut<'a, T> { self.iter_mut() }
+}
+
+/// Only possible if the element type is `Unpin`.
+impl<T: Unpin, A: Allocator> IntoIterator for PinBuffer<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+    
+    fn into_iter(mut self) -> IntoIter<T, A> {
+        // elements are now owned by the iterator
+        let back = replace(&mut self.raw.len, 0);
+        IntoIter { buf: self, front: 0, back }
+    }
+}
+
+/// Iterator over pinned shared refs to a `PinBuffer`.
+pub struct Iter<'a, T>(pub(crate) slice::Iter<'a, T>);
+
+/// Iterator over pinned mutable refs to a `PinBuffer`.
+pub struct IterMut<'a, T>(pub(crate) slice::IterMut<'a, T>);
+
+macro_rules! impl_pin_iter {
+    ($iter:ident, $item:ty)=>{
+        impl<'a, T> Iterator for $iter<'a, T> {
+            type Item = $item;
+            
+            fn next(&mut self) -> Option<$item> {
+                self.0.next().map(|r| unsafe { Pin::new_unchecked(r) })
+            }
+            
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+        
+        impl<'a, T> DoubleEndedIterator for $iter<'a, T> {
+            fn next_back(&mut self) -> Option<$item> {
+                self.0.next_back().map(|r| unsafe { Pin::new_unchecked(r) })
+            }
+        }
+        
+        impl<'a, T> ExactSizeIterator for $iter<'a, T> {}
+    };
+}
+
+impl_pin_iter!(Iter, Pin<&'a T>);
+impl_pin_iter!(IterMut, Pin<&'a mut T>);
+
+/// Owning iterator over a `PinBuffer` of `Unpin` elements.
+pub struct IntoIter<T, A: Allocator = Global> {
+    buf: PinBuffer<T, A>,
+    front: usize,
+    back: usize,
+}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
+    type Item = T;
+    
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back { return None; }
+        self.front += 1;
+        Some(unsafe { self.buf.ptr().add(self.front - 1).read() })
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back { return None; }
+        self.back -= 1;
+        Some(unsafe { self.buf.ptr().add(self.back).read() })
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {}
+
+impl<T, A: Allocator> Drop for IntoIter<T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            drop_rev(self.buf.ptr().add(self.front), self.back - self.front);
+        }
+    }
+}
+
+/// Draining iterator over a `PinBuffer` of `Unpin` elements.
+pub struct Drain<'a, T, A: Allocator = Global> {
+    buf: &'a mut PinBuffer<T, A>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T, A: Allocator> Iterator for Drain<'a, T, A> {
+    type Item = T;
+    
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back { return None; }
+        self.front += 1;
+        Some(unsafe { self.buf.ptr().add(self.front - 1).read() })
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Drain<'a, T, A> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back { return None; }
+        self.back -= 1;
+        Some(unsafe { self.buf.ptr().add(self.back).read() })
+    }
+}
+
+impl<'a, T, A: Allocator> ExactSizeIterator for Drain<'a, T, A> {}
+
+impl<'a, T, A: Allocator> Drop for Drain<'a, T, A> {
+    fn drop(&mut self) {
+        while self.next_back().is_some() {}
+    }
+}
+
+/// Chain of buffers, which drops them top-first.
+///
+/// Like `PinBuffer`, the `Drop` impl lives on a type without `T`,
+/// relying on `PinBuffer` being a transparent wrapper around
+/// `RawBuf`.
+pub(crate) struct BufferChain<T, A: Allocator> {
+    raw: RawBufferChain<A>,
+    _p: PhantomData<T>,
+}
+
+struct RawBufferChain<A: Allocator>(Vec<RawBuf<A>>);
+
+impl<A: Allocator> Drop for RawBufferChain<A> {
+    fn drop(&mut self) {
+        // keep dropping top-first even if dropping a buffer panics
+        struct Rest<'v, A: Allocator>(&'v mut Vec<RawBuf<A>>);
+        
+        impl<'v, A: Allocator> Drop for Rest<'v, A> {
+            fn drop(&mut self) {
+                // only has buffers left if unwinding
+                while let Some(raw) = self.0.pop() {
+                    drop(raw);
+                }
+            }
+        }
+        
+        let rest = Rest(&mut self.0);
+        while let Some(raw) = rest.0.pop() {
+            drop(raw);
+        }
+    }
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for BufferChain<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for BufferChain<T, A> {}
+
+impl<T, A: Allocator> BufferChain<T, A> {
+    pub(crate) fn new() -> Self {
+        BufferChain {
+            raw: RawBufferChain(Vec::new()),
+            _p: PhantomData,
+        }
+    }
+    
+    pub(crate) fn push(&mut self, buffer: PinBuffer<T, A>) {
+        self.raw.0.push(buffer.raw);
+    }
+    
+    /// Drop buffers from the top until only `len` remain.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        while self.raw.0.len() > len {
+            self.raw.0.pop();
+        }
+    }
+    
+    pub(crate) fn into_vec(self) -> Vec<PinBuffer<T, A>> {
+        let mut this = ManuallyDrop::new(self);
+        let raw = ManuallyDrop::new(mem::take(&mut this.raw.0));
+        unsafe {
+            Vec::from_raw_parts(
+                raw.as_ptr() as *mut PinBuffer<T, A>,
+                raw.len(),
+                raw.capacity())
+        }
+    }
+}
+
+impl<T, A: Allocator> Deref for BufferChain<T, A> {
+    type Target = [PinBuffer<T, A>];
+    
+    fn deref(&self) -> &[PinBuffer<T, A>] {
+        unsafe {
+            slice::from_raw_parts(
+                self.raw.0.as_ptr() as *const PinBuffer<T, A>,
+                self.raw.0.len())
+        }
+    }
+}
+
+impl<T, A: Allocator> DerefMut for BufferChain<T, A> {
+    fn deref_mut(&mut self) -> &mut [PinBuffer<T, A>] {
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.raw.0.as_mut_ptr() as *mut PinBuffer<T, A>,
+                self.raw.0.len())
+        }
+    }
+}
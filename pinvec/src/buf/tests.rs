@@ -0,0 +1,47 @@
+use crate::{
+    PinBuffer,
+    test_util::DropLog,
+};
+
+#[test]
+fn truncate_clear() {
+    let log = DropLog::new();
+    let mut buf = PinBuffer::new(10);
+    log.fill(&mut buf, 0..10, PinBuffer::push);
+    
+    buf.truncate(20);
+    assert_eq!(buf.len(), 10);
+    buf.truncate(6);
+    assert_eq!(buf.len(), 6);
+    assert_eq!(log.vals(), [9, 8, 7, 6]);
+    buf.clear();
+    assert!(buf.is_empty());
+    assert_eq!(log.vals(), [9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+}
+
+#[test]
+fn drain() {
+    let log = DropLog::new();
+    let mut buf = PinBuffer::new(10);
+    log.fill(&mut buf, 0..10, PinBuffer::push);
+    
+    let mut drain = buf.drain(4..);
+    assert_eq!(drain.len(), 6);
+    assert_eq!(drain.next().map(|e| e.val), Some(4));
+    assert_eq!(drain.next_back().map(|e| e.val), Some(9));
+    drop(drain);
+    assert_eq!(log.vals(), [4, 9, 8, 7, 6, 5]);
+    assert_eq!(buf.len(), 4);
+    
+    buf.push(log.elem(10));
+    assert_eq!(buf[4].val, 10);
+    assert!(buf.drain(5..).next().is_none());
+}
+
+#[test]
+#[should_panic]
+fn drain_oob() {
+    let mut buf: PinBuffer<u8> = PinBuffer::new(4);
+    buf.push(0);
+    buf.drain(2..);
+}
@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests;
+
+use crate::vec::{calc_index, correct_buffer_size};
+use core::{
+    pin::Pin,
+    mem::MaybeUninit,
+    marker::PhantomData,
+    ptr::{self, drop_in_place},
+};
+use alloc::boxed::Box;
+use pow_of_2::PowOf2;
+
+#[cfg(not(loom))]
+use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+#[cfg(loom)]
+use loom::{
+    sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering},
+    cell::UnsafeCell,
+};
+
+/// `core::cell::UnsafeCell` with the API of `loom::cell::UnsafeCell`.
+#[cfg(not(loom))]
+struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+    fn new(inner: T) -> Self {
+        UnsafeCell(core::cell::UnsafeCell::new(inner))
+    }
+    
+    fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+    
+    fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
+
+/// Element slot, which becomes readable once initialized.
+struct Slot<T> {
+    ready: AtomicBool,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Slot<T> {
+    fn new() -> Self {
+        Slot {
+            ready: AtomicBool::new(false),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+/// Append-only vec-like with stable memory addresses, which can
+/// be pushed to and read from concurrently.
+///
+/// Uses the same layout as `PinVec`, but with buffers allocated
+/// on demand by whichever pushing thread first needs them. 
+/// `push` and `get` take `&self`, and `get` is wait-free.
+pub struct ConcurrentPinVec<T> {
+    buffers: Box<[AtomicPtr<Slot<T>>]>,
+    buf_0_len: PowOf2<usize>,
+    reserved: AtomicUsize,
+    
+    p: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for ConcurrentPinVec<T> {}
+
+unsafe impl<T: Send + Sync> Sync for ConcurrentPinVec<T> {}
+
+impl<T> ConcurrentPinVec<T> {
+    /// New, empty `ConcurrentPinVec`.
+    pub fn new(buf_0_len: PowOf2<usize>) -> Self {
+        let max_buffers = usize::BITS as usize - buf_0_len.exp() as usize;
+        ConcurrentPinVec {
+            buffers: (0..max_buffers)
+                .map(|_| AtomicPtr::new(ptr::null_mut()))
+                .collect(),
+            buf_0_len,
+            reserved: AtomicUsize::new(0),
+            p: PhantomData,
+        }
+    }
+    
+    /// Number of pushes which have begun.
+    ///
+    /// Elements below this index may not be readable yet, if 
+    /// their pushes have not completed.
+    pub fn len(&self) -> usize { self.reserved.load(Ordering::Acquire) }
+    
+    /// Whether no pushes have begun.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    
+    /// Get the buffer at an outer index, allocating it if
+    /// necessary.
+    fn buffer(&self, outer: usize) -> *mut Slot<T> {
+        let buffer = self.buffers[outer].load(Ordering::Acquire);
+        if !buffer.is_null() { return buffer; }
+        
+        let size = correct_buffer_size(self.buf_0_len, outer);
+        let new = (0..size)
+            .map(|_| Slot::new())
+            .collect::<Box<[Slot<T>]>>();
+        let new = Box::into_raw(new) as *mut Slot<T>;
+        match self.buffers[outer].compare_exchange(
+            ptr::null_mut(), new, 
+            Ordering::AcqRel, Ordering::Acquire,
+        ) {
+            Ok(_) => new,
+            Err(existing) => {
+                // another thread allocated it first
+                unsafe { 
+                    drop(Box::from_raw(
+                        ptr::slice_from_raw_parts_mut(new, size)));
+                }
+                existing
+            },
+        }
+    }
+    
+    /// Push an element onto the top of the vector, returning its
+    /// index.
+    ///
+    /// Allocates more memory if necessary, but never moves 
+    /// existing elements.
+    pub fn push(&self, elem: T) -> usize {
+        let index = self.reserved.fetch_add(1, Ordering::AcqRel);
+        let (outer, inner) = calc_index(self.buf_0_len, index);
+        assert!(outer < self.buffers.len(), 
+            "ConcurrentPinVec capacity overflow");
+        
+        unsafe {
+            // this thread has exclusive access to the slot until
+            // it is marked ready
+            let slot = &*self.buffer(outer).add(inner);
+            slot.value.with_mut(|value| (*value).as_mut_ptr().write(elem));
+            slot.ready.store(true, Ordering::Release);
+        }
+        
+        index
+    }
+    
+    /// Get by index as pinned shared ref.
+    ///
+    /// Returns `None` if the index is out of bounds, or the push
+    /// to that index has not yet completed.
+    pub fn get(&self, index: usize) -> Option<Pin<&T>> {
+        if index >= self.len() { return None; }
+        let (outer, inner) = calc_index(self.buf_0_len, index);
+        let buffer = self.buffers[outer].load(Ordering::Acquire);
+        if buffer.is_null() { return None; }
+        
+        unsafe {
+            let slot = &*buffer.add(inner);
+            if !slot.ready.load(Ordering::Acquire) { return None; }
+            Some(slot.value.with(|value| 
+                Pin::new_unchecked(&*(*value).as_ptr())))
+        }
+    }
+}
+
+impl<T> Drop for ConcurrentPinVec<T> {
+    fn drop(&mut self) {
+        // drop top-first
+        for outer in (0..self.buffers.len()).rev() {
+            let buffer = self.buffers[outer].load(Ordering::Acquire);
+            if buffer.is_null() { continue; }
+            let size = correct_buffer_size(self.buf_0_len, outer);
+            
+            unsafe {
+                for inner in (0..size).rev() {
+                    let slot = &*buffer.add(inner);
+                    if slot.ready.load(Ordering::Acquire) {
+                        slot.value.with_mut(|value| 
+                            drop_in_place((*value).as_mut_ptr()));
+                    }
+                }
+                drop(Box::from_raw(
+                    ptr::slice_from_raw_parts_mut(buffer, size)));
+            }
+        }
+    }
+}
+
+impl<T> Default for ConcurrentPinVec<T> {
+    fn default() -> Self {
+        ConcurrentPinVec::new(PowOf2::<usize>::_64)
+    }
+}
@@ -0,0 +1,146 @@
+// loom tests run with:
+//
+//     RUSTFLAGS="--cfg loom" cargo test -p pinvec --release --lib concurrent
+
+use crate::ConcurrentPinVec;
+use pow_of_2::PowOf2;
+
+#[cfg(not(loom))]
+use std::{
+    thread,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(not(loom))]
+#[test]
+fn push_get() {
+    for buf_0_len in (0..10).map(PowOf2::<usize>::from_exp) {
+        let vec: ConcurrentPinVec<usize> = ConcurrentPinVec::new(buf_0_len);
+        assert!(vec.get(0).is_none());
+        let mut addrs = Vec::new();
+        for i in 0..1000 {
+            assert_eq!(vec.push(i), i);
+            addrs.push(&*vec.get(i).unwrap() as *const usize);
+        }
+        assert_eq!(vec.len(), 1000);
+        assert!(vec.get(1000).is_none());
+        assert!(vec.get(usize::MAX).is_none());
+        for (i, &addr) in addrs.iter().enumerate() {
+            let r = vec.get(i).unwrap();
+            assert_eq!(*r, i);
+            assert_eq!(&*r as *const usize, addr);
+        }
+    }
+}
+
+#[cfg(not(loom))]
+#[test]
+fn concurrent_push_get() {
+    const THREADS: usize = 4;
+    const PER_THREAD: usize = 10000;
+    
+    let vec: ConcurrentPinVec<(usize, usize)> = 
+        ConcurrentPinVec::new(PowOf2::<usize>::_1);
+    thread::scope(|s| {
+        for t in 0..THREADS {
+            let vec = &vec;
+            s.spawn(move || {
+                for i in 0..PER_THREAD {
+                    let index = vec.push((t, i));
+                    assert_eq!(*vec.get(index).unwrap(), (t, i));
+                }
+            });
+        }
+        let vec = &vec;
+        s.spawn(move || {
+            // read while others push
+            let mut seen = 0;
+            while seen < THREADS * PER_THREAD {
+                seen = (0..vec.len())
+                    .filter(|&i| vec.get(i).is_some())
+                    .count();
+            }
+        });
+    });
+    
+    let mut counts = [0; THREADS];
+    for i in 0..vec.len() {
+        let (t, n) = *vec.get(i).unwrap();
+        assert_eq!(n, counts[t]);
+        counts[t] += 1;
+    }
+    assert_eq!(counts, [PER_THREAD; THREADS]);
+}
+
+#[cfg(not(loom))]
+#[test]
+fn drop_elems() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    
+    struct D;
+    
+    impl Drop for D {
+        fn drop(&mut self) { DROPS.fetch_add(1, Ordering::SeqCst); }
+    }
+    
+    let vec = ConcurrentPinVec::new(PowOf2::<usize>::_4);
+    for _ in 0..100 {
+        vec.push(D);
+    }
+    drop(vec);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 100);
+}
+
+#[cfg(loom)]
+#[test]
+fn loom_push_push() {
+    use loom::{thread, sync::Arc};
+    
+    loom::model(|| {
+        let vec = Arc::new(ConcurrentPinVec::new(PowOf2::<usize>::_1));
+        let threads: Vec<_> = (0..2)
+            .map(|t| {
+                let vec = Arc::clone(&vec);
+                thread::spawn(move || {
+                    let index = vec.push(t);
+                    assert_eq!(*vec.get(index).unwrap(), t);
+                    index
+                })
+            })
+            .collect();
+        let mut indices: Vec<usize> = threads.into_iter()
+            .map(|t| t.join().unwrap())
+            .collect();
+        indices.sort();
+        assert_eq!(indices, [0, 1]);
+        assert_eq!(*vec.get(0).unwrap() + *vec.get(1).unwrap(), 1);
+    });
+}
+
+#[cfg(loom)]
+#[test]
+fn loom_push_get() {
+    use loom::{thread, sync::Arc};
+    
+    loom::model(|| {
+        let vec = Arc::new(ConcurrentPinVec::new(PowOf2::<usize>::_1));
+        vec.push(String::from("a"));
+        
+        let writer = {
+            let vec = Arc::clone(&vec);
+            thread::spawn(move || {
+                vec.push(String::from("b"));
+                vec.push(String::from("c"));
+            })
+        };
+        
+        // readers see either nothing or a fully written element
+        for (i, expected) in ["a", "b", "c"].iter().enumerate() {
+            if let Some(elem) = vec.get(i) {
+                assert_eq!(&*elem, expected);
+            }
+        }
+        writer.join().unwrap();
+        assert_eq!(&*vec.get(2).unwrap(), "c");
+    });
+}
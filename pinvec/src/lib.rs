@@ -3,15 +3,46 @@
 //! A growable vector-like structure which never moves its contents,
 //! and guarantees this contract through the pin api.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 extern crate pow_of_2;
 
 /// Pinned non-growing buffer.
 pub mod buf;
 
+/// Pinned non-growing inline buffer.
+pub mod array;
+
 /// Pinned growable buffer.
 pub mod vec;
 
+/// Pinned growable buffer, with inline first segment.
+pub mod small;
+
+/// Pinned growable buffer, with concurrent append.
+pub mod concurrent;
+
+/// Typed arena with stable memory addresses.
+pub mod arena;
+
+/// Slab with stable memory addresses.
+pub mod slab;
+
+/// Drop-logging fixtures shared by tests.
+#[cfg(any(test, feature = "test-util"))]
+#[doc(hidden)]
+pub mod test_util;
+
 pub use self::{
     buf::PinBuffer,
-    vec::PinVec,
-};
\ No newline at end of file
+    array::PinArrayBuffer,
+    vec::{PinVec, Retention, Handle},
+    small::SmallPinVec,
+    concurrent::ConcurrentPinVec,
+    arena::PinArena,
+    slab::PinSlab,
+};
+
+/// Allocator API which buffers are allocated through.
+pub use allocator_api2::alloc::{Allocator, Global};
\ No newline at end of file
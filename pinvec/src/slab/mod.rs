@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests;
+
+use crate::vec::{self, PinVec};
+use core::{
+    pin::Pin,
+    marker::Unpin,
+    mem::replace,
+    iter::Enumerate,
+    ops::Index,
+};
+use allocator_api2::alloc::{Allocator, Global};
+use pow_of_2::PowOf2;
+
+/// Slab with stable memory addresses.
+///
+/// Elements can be removed from anywhere, and their slots are 
+/// reused by later inserts. Live elements are never moved.
+pub struct PinSlab<T, A: Allocator = Global> {
+    slots: PinVec<Slot<T>, A>,
+    // most recently vacated slot, which links to the one before
+    free: Option<usize>,
+    len: usize,
+}
+
+enum Slot<T> {
+    Occupied(T),
+    Vacant(Option<usize>),
+}
+
+impl<T> Slot<T> {
+    fn pin_ref(self: Pin<&Self>) -> Option<Pin<&T>> {
+        match self.get_ref() {
+            Slot::Occupied(elem) => Some(unsafe { Pin::new_unchecked(elem) }),
+            Slot::Vacant(_) => None,
+        }
+    }
+    
+    fn pin_mut(self: Pin<&mut Self>) -> Option<Pin<&mut T>> {
+        match unsafe { self.get_unchecked_mut() } {
+            Slot::Occupied(elem) => Some(unsafe { Pin::new_unchecked(elem) }),
+            Slot::Vacant(_) => None,
+        }
+    }
+}
+
+impl<T> PinSlab<T> {
+    /// New, empty `PinSlab`.
+    pub fn new(buf_0_len: PowOf2<usize>) -> Self {
+        PinSlab::new_in(buf_0_len, Global)
+    }
+}
+
+impl<T, A: Allocator> PinSlab<T, A> {
+    /// New, empty `PinSlab`, which allocates buffers in `alloc`.
+    pub fn new_in(buf_0_len: PowOf2<usize>, alloc: A) -> Self {
+        PinSlab {
+            slots: PinVec::new_in(buf_0_len, alloc),
+            free: None,
+            len: 0,
+        }
+    }
+    
+    /// Current number of elements.
+    pub fn len(&self) -> usize { self.len }
+    
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    
+    /// Current capacity in elements, including vacant slots.
+    pub fn capacity(&self) -> usize { self.slots.capacity() }
+    
+    /// Insert an element, reusing a vacant slot if there is one,
+    /// and return its key.
+    pub fn insert(&mut self, elem: T) -> usize 
+    where A: Clone {
+        match self.free {
+            Some(key) => {
+                let next = match *self.slots.idx_ref(key) {
+                    Slot::Vacant(next) => next,
+                    Slot::Occupied(_) => unreachable!("occupied slot in free list"),
+                };
+                self.slots.set(key, Slot::Occupied(elem));
+                self.free = next;
+                self.len += 1;
+                key
+            },
+            None => {
+                self.slots.push(Slot::Occupied(elem));
+                self.len += 1;
+                self.slots.len() - 1
+            },
+        }
+    }
+    
+    /// Remove and drop an element in place.
+    ///
+    /// Return false if there is no element with that key.
+    pub fn remove(&mut self, key: usize) -> bool {
+        if !self.contains(key) { return false; }
+        self.slots.set(key, Slot::Vacant(self.free));
+        self.free = Some(key);
+        self.len -= 1;
+        true
+    }
+    
+    /// Remove and return an element.
+    ///
+    /// Only possible if the element type is `Unpin`.
+    pub fn take(&mut self, key: usize) -> Option<T> 
+    where T: Unpin {
+        if !self.contains(key) { return None; }
+        let slot = replace(&mut self.slots[key], Slot::Vacant(self.free));
+        self.free = Some(key);
+        self.len -= 1;
+        match slot {
+            Slot::Occupied(elem) => Some(elem),
+            Slot::Vacant(_) => unreachable!(),
+        }
+    }
+    
+    /// Whether there is an element with that key.
+    pub fn contains(&self, key: usize) -> bool {
+        matches!(self.slots.get_ref(key).as_deref(), Some(Slot::Occupied(_)))
+    }
+    
+    /// Get by key as pinned shared ref.
+    pub fn get_ref(&self, key: usize) -> Option<Pin<&T>> {
+        self.slots.get_ref(key).and_then(Slot::pin_ref)
+    }
+    
+    /// Get by key as pinned mutable ref.
+    pub fn get_mut(&mut self, key: usize) -> Option<Pin<&mut T>> {
+        self.slots.get_mut(key).and_then(Slot::pin_mut)
+    }
+    
+    /// Iterate over keys and pinned shared refs, in key order.
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        Iter(self.slots.iter().enumerate())
+    }
+    
+    /// Iterate over keys and pinned mutable refs, in key order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, A> {
+        IterMut(self.slots.iter_mut().enumerate())
+    }
+}
+
+impl<T, A: Allocator> Index<usize> for PinSlab<T, A> {
+    type Output = T;
+    
+    fn index(&self, key: usize) -> &T {
+        match self.get_ref(key) {
+            Some(elem) => elem.get_ref(),
+            None => panic!("no element in pinslab with key {}", key),
+        }
+    }
+}
+
+impl<T> Default for PinSlab<T> {
+    fn default() -> Self {
+        PinSlab::new(PowOf2::<usize>::_64)
+    }
+}
+
+/// Iterator over keys and pinned shared refs to a `PinSlab`.
+pub struct Iter<'a, T, A: Allocator = Global>(Enumerate<vec::Iter<'a, Slot<T>, A>>);
+
+/// Iterator over keys and pinned mutable refs to a `PinSlab`.
+pub struct IterMut<'a, T, A: Allocator = Global>(Enumerate<vec::IterMut<'a, Slot<T>, A>>);
+
+impl<'a, T, A: Allocator> Iterator for Iter<'a, T, A> {
+    type Item = (usize, Pin<&'a T>);
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.by_ref()
+            .find_map(|(key, slot)| slot.pin_ref().map(|elem| (key, elem)))
+    }
+}
+
+impl<'a, T, A: Allocator> Iterator for IterMut<'a, T, A> {
+    type Item = (usize, Pin<&'a mut T>);
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.by_ref()
+            .find_map(|(key, slot)| slot.pin_mut().map(|elem| (key, elem)))
+    }
+}
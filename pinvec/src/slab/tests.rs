@@ -0,0 +1,78 @@
+use crate::{
+    PinSlab,
+    test_util::DropLog,
+};
+use pow_of_2::PowOf2;
+
+#[test]
+fn insert_remove() {
+    let mut slab = PinSlab::new(PowOf2::<usize>::_4);
+    let keys: Vec<usize> = (0..20).map(|i| slab.insert(i)).collect();
+    assert_eq!(keys, (0..20).collect::<Vec<_>>());
+    assert_eq!(slab.len(), 20);
+    
+    assert!(slab.remove(3));
+    assert!(!slab.remove(3));
+    assert!(slab.remove(17));
+    assert_eq!(slab.take(5), Some(5));
+    assert_eq!(slab.take(5), None);
+    assert!(!slab.remove(20));
+    assert_eq!(slab.len(), 17);
+    assert!(!slab.contains(3));
+    assert!(slab.get_ref(17).is_none());
+    assert_eq!(*slab.get_ref(4).unwrap(), 4);
+    *slab.get_mut(4).unwrap() += 400;
+    assert_eq!(slab[4], 404);
+    
+    // freed slots are reused most-recent-first
+    let capacity = slab.capacity();
+    assert_eq!(slab.insert(100), 5);
+    assert_eq!(slab.insert(101), 17);
+    assert_eq!(slab.insert(102), 3);
+    assert_eq!(slab.insert(103), 20);
+    assert_eq!(slab.capacity(), capacity);
+    
+    let elems: Vec<(usize, usize)> = slab.iter()
+        .map(|(key, elem)| (key, *elem))
+        .collect();
+    assert_eq!(elems.len(), 21);
+    assert_eq!(elems[3], (3, 102));
+    assert_eq!(elems[20], (20, 103));
+}
+
+#[test]
+fn addrstable() {
+    let mut slab = PinSlab::new(PowOf2::<usize>::_2);
+    let mut addrs = Vec::new();
+    for i in 0..50usize {
+        let key = slab.insert(i);
+        addrs.push(&*slab.get_ref(key).unwrap() as *const usize);
+    }
+    for key in (0..50).step_by(3) {
+        slab.remove(key);
+    }
+    for i in 0..50 {
+        slab.insert(i);
+    }
+    for (key, elem) in slab.iter() {
+        if key < 50 && key % 3 != 0 {
+            assert_eq!(&*elem as *const usize, addrs[key]);
+            assert_eq!(*elem, key);
+        }
+    }
+}
+
+#[test]
+fn drop_in_place() {
+    let log = DropLog::new();
+    let mut slab = PinSlab::new(PowOf2::<usize>::_4);
+    log.fill(&mut slab, 0..10, |slab, elem| { slab.insert(elem); });
+    slab.remove(4);
+    slab.remove(7);
+    assert_eq!(log.vals(), [4, 7]);
+    for (_, mut elem) in slab.iter_mut() {
+        elem.val += 100;
+    }
+    drop(slab);
+    assert_eq!(log.vals(), [4, 7, 109, 108, 106, 105, 103, 102, 101, 100]);
+}
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    array::PinArrayBuffer,
+    vec::PinVec,
+};
+use core::{
+    pin::Pin,
+    marker::Unpin,
+};
+use allocator_api2::alloc::{Allocator, Global};
+use pow_of_2::PowOf2;
+
+/// Growable vec-like with stable memory addresses, which stores
+/// its first `N` elements inline.
+///
+/// Elements beyond the first `N` overflow into a `PinVec`. As
+/// moving this moves the inline elements, methods which hand
+/// out pinned references require it to be pinned.
+pub struct SmallPinVec<T, const N: usize, A: Allocator = Global> {
+    // declared first so that it's dropped first, as it holds
+    // the top elements
+    tail: PinVec<T, A>,
+    head: PinArrayBuffer<T, N>,
+}
+
+impl<T, const N: usize> SmallPinVec<T, N> {
+    /// New, empty `SmallPinVec`, with the given length for the
+    /// first heap-allocated buffer.
+    pub fn new(buf_0_len: PowOf2<usize>) -> Self {
+        SmallPinVec::new_in(buf_0_len, Global)
+    }
+}
+
+impl<T, const N: usize, A: Allocator> SmallPinVec<T, N, A> {
+    /// New, empty `SmallPinVec`, which allocates buffers in
+    /// `alloc`.
+    pub fn new_in(buf_0_len: PowOf2<usize>, alloc: A) -> Self {
+        SmallPinVec {
+            tail: PinVec::new_in(buf_0_len, alloc),
+            head: PinArrayBuffer::new(),
+        }
+    }
+    
+    fn project(self: Pin<&mut Self>) -> (Pin<&mut PinArrayBuffer<T, N>>, &mut PinVec<T, A>) {
+        unsafe {
+            let this = self.get_unchecked_mut();
+            (Pin::new_unchecked(&mut this.head), &mut this.tail)
+        }
+    }
+    
+    fn head(self: Pin<&Self>) -> Pin<&PinArrayBuffer<T, N>> {
+        unsafe { self.map_unchecked(|this| &this.head) }
+    }
+    
+    /// Current length in elements.
+    pub fn len(&self) -> usize { self.head.len() + self.tail.len() }
+    
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+    
+    /// Current capacity in elements, inline and on the heap.
+    pub fn capacity(&self) -> usize { N + self.tail.capacity() }
+    
+    /// Push an element onto the top of the vector.
+    ///
+    /// Only allocates once the inline buffer is full.
+    pub fn push(self: Pin<&mut Self>, elem: T)
+    where A: Clone {
+        let (head, tail) = self.project();
+        if head.can_push() {
+            head.push(elem);
+        } else {
+            tail.push(elem);
+        }
+    }
+    
+    /// Pop and drop the top element.
+    ///
+    /// Return false if already empty.
+    pub fn remove_top(self: Pin<&mut Self>) -> bool {
+        let (head, tail) = self.project();
+        tail.remove_top() || head.remove_top()
+    }
+    
+    /// Pop and return the top element.
+    ///
+    /// Only possible if the element type is `Unpin`.
+    pub fn pop(&mut self) -> Option<T>
+    where T: Unpin {
+        self.tail.pop().or_else(|| self.head.pop())
+    }
+    
+    /// Override an existing element.
+    ///
+    /// See `PinVec::set`.
+    pub fn set(self: Pin<&mut Self>, index: usize, elem: T) {
+        let len = self.len();
+        if self.try_set(index, elem).is_err() {
+            panic!("index {} out of bounds, pinvec length = {}", index, len);
+        }
+    }
+    
+    /// Override an existing element, or give it back if the
+    /// index is out of bounds.
+    pub fn try_set(self: Pin<&mut Self>, index: usize, elem: T) -> Result<(), T> {
+        let (head, tail) = self.project();
+        if index < N {
+            head.try_set(index, elem)
+        } else {
+            tail.try_set(index - N, elem)
+        }
+    }
+    
+    /// Get by index as pinned shared ref, or panic.
+    pub fn idx_ref(self: Pin<&Self>, index: usize) -> Pin<&T> {
+        let len = self.len();
+        self.try_idx_ref(index).unwrap_or_else(|| panic!(
+            "index {} out of bounds, pinvec length = {}", index, len))
+    }
+    
+    /// Get by index as pinned mutable ref, or panic.
+    pub fn idx_mut(self: Pin<&mut Self>, index: usize) -> Pin<&mut T> {
+        let len = self.len();
+        self.try_idx_mut(index).unwrap_or_else(|| panic!(
+            "index {} out of bounds, pinvec length = {}", index, len))
+    }
+    
+    /// Get by index as pinned shared ref.
+    pub fn try_idx_ref(self: Pin<&Self>, index: usize) -> Option<Pin<&T>> {
+        if index < N {
+            self.head().try_idx_ref(index)
+        } else {
+            Pin::get_ref(self).tail.get_ref(index - N)
+        }
+    }
+    
+    /// Get by index as pinned mutable ref.
+    pub fn try_idx_mut(self: Pin<&mut Self>, index: usize) -> Option<Pin<&mut T>> {
+        let (head, tail) = self.project();
+        if index < N {
+            head.try_idx_mut(index)
+        } else {
+            tail.get_mut(index - N)
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallPinVec<T, N> {
+    fn default() -> Self {
+        SmallPinVec::new(PowOf2::<usize>::_64)
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{
+    SmallPinVec,
+    test_util::assert_drops_top_first,
+};
+use std::{
+    pin::Pin,
+    marker::PhantomPinned,
+};
+use pow_of_2::PowOf2;
+
+#[test]
+fn inline_then_heap() {
+    let mut vec: SmallPinVec<usize, 8> = SmallPinVec::new(PowOf2::<usize>::_4);
+    let mut vec = Pin::new(&mut vec);
+    for i in 0..8 {
+        vec.as_mut().push(i);
+    }
+    assert_eq!(vec.len(), 8);
+    assert_eq!(vec.capacity(), 8);
+    
+    for i in 8..100 {
+        vec.as_mut().push(i);
+    }
+    assert_eq!(vec.len(), 100);
+    assert!(vec.capacity() >= 100);
+    for i in 0..100 {
+        assert_eq!(*vec.as_ref().idx_ref(i), i);
+    }
+    assert!(vec.as_ref().try_idx_ref(100).is_none());
+    
+    vec.as_mut().set(3, 300);
+    vec.as_mut().set(30, 3000);
+    *vec.as_mut().idx_mut(4) += 400;
+    assert_eq!(*vec.as_ref().idx_ref(3), 300);
+    assert_eq!(*vec.as_ref().idx_ref(30), 3000);
+    assert_eq!(*vec.as_ref().idx_ref(4), 404);
+    assert_eq!(vec.as_mut().try_set(100, 0), Err(0));
+    
+    for i in (31..100).rev() {
+        assert_eq!(vec.pop(), Some(i));
+    }
+    while vec.as_mut().remove_top() {}
+    assert!(vec.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn idx_oob() {
+    let mut vec: SmallPinVec<usize, 4> = SmallPinVec::default();
+    let mut vec = Pin::new(&mut vec);
+    vec.as_mut().push(0);
+    vec.as_ref().idx_ref(1);
+}
+
+#[test]
+fn no_inline() {
+    let mut vec: SmallPinVec<u32, 0> = SmallPinVec::default();
+    let mut vec = Pin::new(&mut vec);
+    assert!(vec.is_empty());
+    vec.as_mut().push(7);
+    assert_eq!(vec.len(), 1);
+    assert!(!vec.is_empty());
+    assert_eq!(*vec.as_ref().idx_ref(0), 7);
+    assert!(vec.as_mut().remove_top());
+    assert!(vec.is_empty());
+}
+
+#[test]
+fn addrstable() {
+    struct Elem(usize, PhantomPinned);
+    
+    let mut vec: Pin<Box<SmallPinVec<Elem, 16>>> = 
+        Box::pin(SmallPinVec::new(PowOf2::<usize>::_2));
+    let mut addrs = Vec::new();
+    for i in 0..1000 {
+        vec.as_mut().push(Elem(i, PhantomPinned));
+        addrs.push(&*vec.as_ref().idx_ref(i) as *const Elem);
+        
+        if i % 100 == 0 {
+            for (j, &addr) in addrs.iter().enumerate() {
+                let elem = vec.as_ref().idx_ref(j);
+                assert_eq!(elem.0, j);
+                assert_eq!(&*elem as *const Elem, addr);
+            }
+        }
+    }
+}
+
+#[test]
+fn drop_order() {
+    let vec: Pin<Box<SmallPinVec<_, 8>>> = 
+        Box::pin(SmallPinVec::new(PowOf2::<usize>::_4));
+    assert_drops_top_first(100, vec, |vec, elem| vec.as_mut().push(elem));
+}
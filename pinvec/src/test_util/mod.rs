@@ -0,0 +1,94 @@
+use alloc::{
+    rc::Rc,
+    vec::Vec,
+};
+use core::{
+    cell::RefCell,
+    ops::Range,
+};
+
+/// Shared log of element values, in the order they were dropped.
+#[derive(Clone, Default)]
+pub struct DropLog(Rc<RefCell<Vec<usize>>>);
+
+/// Element which logs its value to a `DropLog` when dropped.
+pub struct Logged {
+    pub val: usize,
+    log: DropLog,
+    bomb: bool,
+}
+
+impl DropLog {
+    /// New, empty log.
+    pub fn new() -> Self {
+        DropLog::default()
+    }
+    
+    /// Element which logs `val` when dropped.
+    pub fn elem(&self, val: usize) -> Logged {
+        Logged { val, log: self.clone(), bomb: false }
+    }
+    
+    /// Element which logs `val` and then panics when dropped.
+    pub fn bomb(&self, val: usize) -> Logged {
+        Logged { val, log: self.clone(), bomb: true }
+    }
+    
+    /// Push elements logging `vals` through `push`.
+    pub fn fill<C, F>(&self, container: &mut C, vals: Range<usize>, mut push: F)
+    where
+        F: FnMut(&mut C, Logged),
+    {
+        for val in vals {
+            push(container, self.elem(val));
+        }
+    }
+    
+    /// Values logged so far.
+    pub fn vals(&self) -> Vec<usize> {
+        self.0.borrow().clone()
+    }
+    
+    /// Number of elements dropped so far.
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+    
+    /// Whether no elements were dropped so far.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    /// Forget values logged so far.
+    pub fn clear(&self) {
+        self.0.borrow_mut().clear();
+    }
+    
+    /// Assert that exactly the elements `vals` were dropped, 
+    /// top-first, then clear the log.
+    pub fn assert_dropped_rev(&self, vals: Range<usize>) {
+        assert!(
+            self.0.borrow().iter().copied().eq(vals.clone().rev()),
+            "expected {:?} dropped top-first, got {:?}", vals, self.vals());
+        self.clear();
+    }
+}
+
+impl Drop for Logged {
+    fn drop(&mut self) {
+        self.log.0.borrow_mut().push(self.val);
+        if self.bomb { panic!("bomb {}", self.val); }
+    }
+}
+
+/// Push `0..n` into `container`, drop it, and assert that its 
+/// elements were dropped top-first.
+pub fn assert_drops_top_first<C, F>(n: usize, mut container: C, push: F)
+where
+    F: FnMut(&mut C, Logged),
+{
+    let log = DropLog::new();
+    log.fill(&mut container, 0..n, push);
+    drop(container);
+    log.assert_dropped_rev(0..n);
+}
@@ -1,13 +1,23 @@
 
 #[cfg(test)]
+#[allow(clippy::legacy_numeric_constants)]
 mod tests;
+#[cfg(feature = "serde")]
+mod serde;
 
-use crate::buf::PinBuffer;
-use std::{
-    usize,
+use crate::buf::{self, PinBuffer, BufferChain};
+use core::{
     pin::Pin,
+    mem::MaybeUninit,
+    marker::PhantomData,
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
+    ops::{Index, IndexMut, RangeFrom},
+    slice,
 };
+use alloc::vec::{self, Vec};
 use pow_of_2::PowOf2;
+use allocator_api2::alloc::{Allocator, Global};
 
 /// Growable vec-like with stable memory addresses.
 ///
@@ -18,10 +28,90 @@ use pow_of_2::PowOf2;
 /// the last, and increases capacity by allocating another 
 /// vector for new elements to overflow into, without 
 /// invalidating existing elements.
-pub struct PinVec<T> {
-    buffers: Vec<PinBuffer<T>>,
+///
+/// Element buffers are allocated in `A`, while the short list
+/// of buffers itself uses the global allocator.
+///
+/// When dropped, elements are dropped top-first, so that no 
+/// element outlives one below it.
+pub struct PinVec<T, A: Allocator = Global> {
+    buffers: BufferChain<T, A>,
     buf_0_len: PowOf2<usize>,
     len: usize,
+    retention: Retention,
+    alloc: A,
+    // number of times the element at each index was removed or
+    // overwritten through set or try_set, only tracked up to the
+    // highest index a handle was made for
+    generations: Vec<u64>,
+}
+
+/// Index of an element in a `PinVec`, which remembers which 
+/// element it was made for.
+///
+/// The handle becomes stale once its element is removed by 
+/// `remove_top`, `pop`, `pop_unchecked`, `truncate`, `clear` or
+/// `drain`, or overwritten by `set` or `try_set`. Looking up a
+/// stale handle returns `None`, even if another element has 
+/// since taken its index.
+///
+/// No other writes are tracked. Writing through a mutable 
+/// reference, such as with `Pin::set` on the result of 
+/// `idx_mut`, `get_mut`, `last_mut`, `iter_mut` or
+/// `get_mut_by_handle`, or through `IndexMut`, counts as
+/// modifying the same element, so its handles stay valid.
+pub struct Handle<T> {
+    index: usize,
+    generation: u64,
+    p: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// Index of the element the handle was made for.
+    pub fn index(&self) -> usize { self.index }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self { *self }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> Debug for Handle<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// What a `PinVec` does with buffers which removing
+/// elements leaves empty.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Retention {
+    /// Free buffers as soon as they become empty.
+    #[default]
+    Free,
+    /// Keep one empty buffer above the top element, so that
+    /// pushing and popping around a buffer boundary doesn't
+    /// repeatedly allocate and free it.
+    KeepSpare,
 }
 
 /// Assert that an index is in bounds, then make a 
@@ -36,56 +126,164 @@ macro_rules! valid_index {
     }};
 }
 
+/// Compute the outer and inner indices where an element
+/// would go, by element index, in a chain of buffers each
+/// twice as big as the last.
+pub(crate) fn calc_index(buf_0_len: PowOf2<usize>, elem_i: usize) -> (usize, usize) {
+    // buffer n begins at buffer unit 2^n - 1
+    let buf_unit_i = elem_i >> buf_0_len.exp();
+    let outer = PowOf2::<usize>::floor(buf_unit_i + 1)
+        .expect("element index overflow")
+        .exp() as usize;
+    let inner = elem_i - (
+        (1 << outer << buf_0_len.exp()) 
+            - buf_0_len.to_uint());
+    (outer, inner)
+}
+
+/// Compute the correct capacity for a buffer in a chain, 
+/// given its outer index.
+pub(crate) fn correct_buffer_size(buf_0_len: PowOf2<usize>, outer_i: usize) -> usize {
+    1 << buf_0_len.exp() << outer_i
+}
+
 impl<T> PinVec<T> {
     /// New, empty `PinVec`.
     pub fn new(buf_0_len: PowOf2<usize>) -> Self {
+        PinVec::new_in(buf_0_len, Global)
+    }
+    
+    /// New, empty `PinVec`, with the given buffer retention 
+    /// policy.
+    pub fn with_retention(buf_0_len: PowOf2<usize>, retention: Retention) -> Self {
+        PinVec::with_retention_in(buf_0_len, retention, Global)
+    }
+}
+
+impl<T, A: Allocator> PinVec<T, A> {
+    /// New, empty `PinVec`, which allocates buffers in `alloc`.
+    pub fn new_in(buf_0_len: PowOf2<usize>, alloc: A) -> Self {
+        PinVec::with_retention_in(buf_0_len, Retention::default(), alloc)
+    }
+    
+    /// New, empty `PinVec`, which allocates buffers in `alloc`, 
+    /// with the given buffer retention policy.
+    pub fn with_retention_in(
+        buf_0_len: PowOf2<usize>,
+        retention: Retention,
+        alloc: A,
+    ) -> Self {
         PinVec {
-            buffers: Vec::new(),
+            buffers: BufferChain::new(),
             buf_0_len,
             len: 0,
+            retention,
+            alloc,
+            generations: Vec::new(),
         }
     }
     
+    /// The allocator buffers are allocated in.
+    pub fn allocator(&self) -> &A { &self.alloc }
+    
     /// Compute the outer and inner indices where an 
     /// element would go, by element index.
     fn calc_index(&self, elem_i: usize) -> (usize, usize) {
-        let buf_unit_i = elem_i >> self.buf_0_len.exp();
-        let outer = {
-            let mut t = 1;
-            while (buf_unit_i + 1) >= (1 << t) {
-                t += 1;
-            }
-            t - 1
-        };
-        let inner = elem_i - (
-            (1 << outer << self.buf_0_len.exp()) 
-                - self.buf_0_len.to_uint());
-        (outer, inner)
+        calc_index(self.buf_0_len, elem_i)
     }
     
     /// Compute the correct capacity for a buffer, given 
     /// its outer index.
     fn correct_buffer_size(&self, outer_i: usize) -> usize {
-        1 << self.buf_0_len.exp() << outer_i
+        correct_buffer_size(self.buf_0_len, outer_i)
     }
     
     /// Current length in elements.
     pub fn len(&self) -> usize { self.len }
     
+    /// Whether there are no elements.
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    
+    /// Current capacity in elements, including that of 
+    /// reserved and spare buffers.
+    pub fn capacity(&self) -> usize {
+        (0..self.buffers.len())
+            .map(|outer| self.correct_buffer_size(outer))
+            .sum()
+    }
+    
+    /// Current buffer retention policy.
+    pub fn retention(&self) -> Retention { self.retention }
+    
+    /// Change the buffer retention policy. 
+    ///
+    /// Takes effect the next time a buffer becomes empty.
+    pub fn set_retention(&mut self, retention: Retention) {
+        self.retention = retention;
+    }
+    
+    /// Allocate buffers such that at least `additional` more 
+    /// elements can be pushed without allocating.
+    ///
+    /// Reserved buffers above the top element may later be 
+    /// freed by removing elements, according to the retention 
+    /// policy.
+    pub fn reserve(&mut self, additional: usize) 
+    where A: Clone {
+        let needed = self.len.checked_add(additional)
+            .expect("capacity overflow");
+        if needed == 0 { return; }
+        let (outer, _) = self.calc_index(needed - 1);
+        while self.buffers.len() <= outer {
+            self.push_buffer();
+        }
+    }
+    
+    /// Allocate the next buffer in the chain.
+    fn push_buffer(&mut self) 
+    where A: Clone {
+        let size = self.correct_buffer_size(self.buffers.len());
+        self.buffers.push(PinBuffer::new_in(size, self.alloc.clone()));
+    }
+    
+    /// Free all buffers which hold no elements.
+    pub fn shrink_to_fit(&mut self) {
+        let used = self.used_buffers();
+        self.buffers.truncate(used);
+    }
+    
+    /// Number of buffers which hold elements.
+    fn used_buffers(&self) -> usize {
+        match self.len {
+            0 => 0,
+            len => self.calc_index(len - 1).0 + 1,
+        }
+    }
+    
+    /// Free empty buffers beyond what the retention policy 
+    /// allows.
+    fn trim_buffers(&mut self) {
+        let keep = match self.retention {
+            Retention::Free => self.used_buffers(),
+            Retention::KeepSpare => self.used_buffers() + 1,
+        };
+        self.buffers.truncate(keep);
+    }
+    
     /// Push an element onto the top of the vector.
     ///
     /// Allocates more  memory if necessary, but never
     /// moves existing elements do, as that would violate
     /// `Pin` invariants.
-    pub fn push(&mut self, elem: T) {
+    pub fn push(&mut self, elem: T) 
+    where A: Clone {
         // elem's index will be current length
         let (outer, inner) = self.calc_index(self.len());
         
         // potentially add a new buffer
         if outer >= self.buffers.len() {
             debug_assert_eq!(outer, self.buffers.len());
-            self.buffers.push(PinBuffer::new(
-                self.correct_buffer_size(outer)));
+            self.push_buffer();
         }
         
         // push to buffer
@@ -97,6 +295,82 @@ impl<T> PinVec<T> {
         self.len += 1;
     }
     
+    /// Push an element onto the top of the vector, and get a 
+    /// handle to it.
+    pub fn push_handle(&mut self, elem: T) -> Handle<T>
+    where A: Clone {
+        self.push(elem);
+        self.handle(self.len - 1).unwrap()
+    }
+    
+    /// Get a handle to an existing element.
+    pub fn handle(&mut self, index: usize) -> Option<Handle<T>> {
+        if index >= self.len { return None; }
+        if self.generations.len() <= index {
+            self.generations.resize(index + 1, 0);
+        }
+        Some(Handle {
+            index,
+            generation: self.generations[index],
+            p: PhantomData,
+        })
+    }
+    
+    /// Whether the element a handle was made for is still present.
+    pub fn contains_handle(&self, handle: Handle<T>) -> bool {
+        handle.index < self.len 
+            && self.generation(handle.index) == handle.generation
+    }
+    
+    /// Get by handle as pinned shared ref, or `None` if stale.
+    pub fn get_by_handle(&self, handle: Handle<T>) -> Option<Pin<&T>> {
+        if !self.contains_handle(handle) { return None; }
+        self.get_ref(handle.index)
+    }
+    
+    /// Get by handle as pinned mutable ref, or `None` if stale.
+    pub fn get_mut_by_handle(&mut self, handle: Handle<T>) -> Option<Pin<&mut T>> {
+        if !self.contains_handle(handle) { return None; }
+        self.get_mut(handle.index)
+    }
+    
+    fn generation(&self, index: usize) -> u64 {
+        self.generations.get(index).copied().unwrap_or(0)
+    }
+    
+    /// Make handles to elements in a range of indices stale.
+    fn bump_generations(&mut self, start: usize, end: usize) {
+        let end = end.min(self.generations.len());
+        if start < end {
+            for generation in &mut self.generations[start..end] {
+                *generation += 1;
+            }
+        }
+    }
+    
+    /// Push an element by initializing it in place, for types 
+    /// which must know their address when constructed.
+    ///
+    /// See `PinBuffer::push_with`.
+    pub fn push_with<F>(&mut self, init: F) -> Pin<&mut T>
+    where
+        A: Clone,
+        F: for<'s> FnOnce(Pin<&'s mut MaybeUninit<T>>) -> Pin<&'s mut T>,
+    {
+        let (outer, inner) = self.calc_index(self.len());
+        if outer >= self.buffers.len() {
+            debug_assert_eq!(outer, self.buffers.len());
+            self.push_buffer();
+        }
+        
+        let buffer = &mut self.buffers[outer];
+        debug_assert_eq!(buffer.len(), inner);
+        let elem = buffer.push_with(init);
+        
+        self.len += 1;
+        elem
+    }
+    
     /// Pop and drop the top element. 
     ///
     /// Return false if already empty.
@@ -105,21 +379,19 @@ impl<T> PinVec<T> {
     pub fn remove_top(&mut self) -> bool {
         if self.len == 0 { return false; }
         
-        let top_buffer_i = self.buffers.len() - 1;
-        let top_buffer = &mut self.buffers[top_buffer_i];
+        let (outer, inner) = self.calc_index(self.len - 1);
+        let top_buffer = &mut self.buffers[outer];
         
         // remove top element
-        debug_assert!(top_buffer.len() > 0);
+        debug_assert_eq!(top_buffer.len(), inner + 1);
         top_buffer.remove_top();
         
-        // potentially remove top buffer
-        // (in addition to reducing memory footprint, other
-        //  assertions rely on the invariant that all 
-        //  buffers will be nonempty of elements)
-        if top_buffer.len() == 0 { self.buffers.pop(); }
-        
         // maintain tracking data
         self.len -= 1;
+        self.bump_generations(self.len, self.len + 1);
+        
+        // potentially free emptied buffers
+        if inner == 0 { self.trim_buffers(); }
         
         true
     }
@@ -132,36 +404,100 @@ impl<T> PinVec<T> {
         // mostly copy of PinVec::remove_top
         
         if self.len == 0 { return None; }
-        let top_buffer_i = self.buffers.len() - 1;
-        let top_buffer = &mut self.buffers[top_buffer_i];
+        let (outer, inner) = self.calc_index(self.len - 1);
+        let top_buffer = &mut self.buffers[outer];
         
-        debug_assert!(top_buffer.len() > 0);
+        debug_assert_eq!(top_buffer.len(), inner + 1);
         let output = top_buffer.pop();
         
-        if top_buffer.len() == 0 { self.buffers.pop(); }
         self.len -= 1;
+        self.bump_generations(self.len, self.len + 1);
+        if inner == 0 { self.trim_buffers(); }
         
         output
     }
     
     /// Pop and return the top element, ignoring `Pin` 
     /// invariants.
+    ///
+    /// # Safety
+    ///
+    /// The caller must uphold the `Pin` contract for the
+    /// moved-out element themselves.
     pub unsafe fn pop_unchecked(&mut self) -> Option<T> {
         // mostly copy of PinVec::remove_top
         
         if self.len == 0 { return None; }
-        let top_buffer_i = self.buffers.len() - 1;
-        let top_buffer = &mut self.buffers[top_buffer_i];
+        let (outer, inner) = self.calc_index(self.len - 1);
+        let top_buffer = &mut self.buffers[outer];
         
-        debug_assert!(top_buffer.len() > 0);
+        debug_assert_eq!(top_buffer.len(), inner + 1);
         let output = top_buffer.pop_unchecked();
         
-        if top_buffer.len() == 0 { self.buffers.pop(); }
         self.len -= 1;
+        self.bump_generations(self.len, self.len + 1);
+        if inner == 0 { self.trim_buffers(); }
         
         output
     }
     
+    /// Drop elements from the top until only `len` remain.
+    ///
+    /// Elements are dropped in place, top-first. Does nothing if
+    /// there are already no more than `len` elements.
+    pub fn truncate(&mut self, len: usize) {
+        self.bump_generations(len, self.len);
+        while self.len > len {
+            // locate once per buffer, rather than per element
+            let (outer, inner) = self.calc_index(self.len - 1);
+            let keep = (inner + 1).saturating_sub(self.len - len);
+            let buffer = &mut self.buffers[outer];
+            while buffer.len() > keep {
+                self.len -= 1;
+                buffer.remove_top();
+            }
+        }
+        self.trim_buffers();
+    }
+    
+    /// Drop all elements, top-first.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+    
+    /// Remove and iterate over elements from `range.start` to the
+    /// top. Elements not iterated over are dropped top-first when
+    /// the iterator is dropped.
+    ///
+    /// Only possible if the element type is `Unpin`.
+    ///
+    /// Panics if the start is out of bounds.
+    pub fn drain(&mut self, range: RangeFrom<usize>) -> Drain<'_, T, A>
+    where T: Unpin {
+        let len = self.len;
+        assert!(range.start <= len, "drain start {} out of bounds, \
+            pinvec length = {}", range.start, len);
+        
+        // elements are now owned by the iterator
+        self.bump_generations(range.start, len);
+        let mut i = len;
+        while i > range.start {
+            let (outer, inner) = self.calc_index(i - 1);
+            let keep = (inner + 1).saturating_sub(i - range.start);
+            unsafe { self.buffers[outer].set_len(keep); }
+            i -= inner + 1 - keep;
+        }
+        self.len = range.start;
+        
+        Drain { vec: self, front: range.start, back: len }
+    }
+    
+    /// Pointer to an element slot, which need not be occupied.
+    fn slot_ptr(&self, index: usize) -> *mut T {
+        let (outer, inner) = self.calc_index(index);
+        unsafe { self.buffers[outer].ptr().add(inner) }
+    }
+    
     /// Override an existing element.
     ///
     /// Quoting [std::pin::Pin](https://doc.rust-lang.org/std/pin/struct.Pin.html#method.set):
@@ -172,6 +508,7 @@ impl<T> PinVec<T> {
     pub fn set(&mut self, index: usize, elem: T) {
         let (outer, inner) = valid_index!(self, index);
         self.buffers[outer].set(inner, elem);
+        self.bump_generations(index, index + 1);
     }
     
     /// Get by index as pinned shared ref, or panic.
@@ -188,15 +525,263 @@ impl<T> PinVec<T> {
     
     /// Get by index as pinned shared ref.
     pub fn get_ref(&self, index: usize) -> Option<Pin<&T>> {
-        let (outer, inner) = valid_index!(self, index);
+        if index >= self.len { return None; }
+        let (outer, inner) = self.calc_index(index);
         self.buffers[outer].get_ref(inner)
     }
     
     /// Get by index as pinned mutable ref.
     pub fn get_mut(&mut self, index: usize) -> Option<Pin<&mut T>> {
-        let (outer, inner) = valid_index!(self, index);
+        if index >= self.len { return None; }
+        let (outer, inner) = self.calc_index(index);
         self.buffers[outer].get_mut(inner)
     }
+    
+    /// Get the bottom element as pinned shared ref.
+    pub fn first_ref(&self) -> Option<Pin<&T>> {
+        self.buffers.first().and_then(|b| b.first_ref())
+    }
+    
+    /// Get the bottom element as pinned mutable ref.
+    pub fn first_mut(&mut self) -> Option<Pin<&mut T>> {
+        self.buffers.first_mut().and_then(|b| b.first_mut())
+    }
+    
+    /// Get the top element as pinned shared ref.
+    pub fn last_ref(&self) -> Option<Pin<&T>> {
+        match self.len {
+            0 => None,
+            len => self.get_ref(len - 1),
+        }
+    }
+    
+    /// Get the top element as pinned mutable ref.
+    pub fn last_mut(&mut self) -> Option<Pin<&mut T>> {
+        match self.len {
+            0 => None,
+            len => self.get_mut(len - 1),
+        }
+    }
+    
+    /// Override an existing element, or give it back if the 
+    /// index is out of bounds.
+    ///
+    /// See `PinVec::set`.
+    pub fn try_set(&mut self, index: usize, elem: T) -> Result<(), T> {
+        if index >= self.len { return Err(elem); }
+        let (outer, inner) = self.calc_index(index);
+        self.buffers[outer].set(inner, elem);
+        self.bump_generations(index, index + 1);
+        Ok(())
+    }
+    
+    /// Iterate as pinned shared refs.
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        Iter(FlatIter::new(self.buffers.iter(), self.len))
+    }
+    
+    /// Iterate as pinned mutable refs.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, A> {
+        IterMut(FlatIter::new(self.buffers.iter_mut(), self.len))
+    }
+}
+
+impl<T, A: Allocator> Index<usize> for PinVec<T, A> {
+    type Output = T;
+    
+    fn index(&self, index: usize) -> &T {
+        let (outer, inner) = valid_index!(self, index);
+        &self.buffers[outer][inner]
+    }
+}
+
+/// Only possible if the element type is `Unpin`.
+impl<T: Unpin, A: Allocator> IndexMut<usize> for PinVec<T, A> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let (outer, inner) = valid_index!(self, index);
+        &mut self.buffers[outer][inner]
+    }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a PinVec<T, A> {
+    type Item = Pin<&'a T>;
+    type IntoIter = Iter<'a, T, A>;
+    
+    fn into_iter(self) -> Iter<'a, T, A> { self.iter() }
+}
+
+impl<'a, T, A: Allocator> IntoIterator for &'a mut PinVec<T, A> {
+    type Item = Pin<&'a mut T>;
+    type IntoIter = IterMut<'a, T, A>;
+    
+    fn into_iter(self) -> IterMut<'a, T, A> { self.iter_mut() }
+}
+
+/// Only possible if the element type is `Unpin`.
+impl<T: Unpin, A: Allocator> IntoIterator for PinVec<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+    
+    fn into_iter(self) -> IntoIter<T, A> {
+        IntoIter(FlatIter::new(self.buffers.into_vec().into_iter(), self.len))
+    }
+}
+
+/// Draining iterator over a `PinVec` of `Unpin` elements.
+pub struct Drain<'a, T, A: Allocator = Global> {
+    vec: &'a mut PinVec<T, A>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T, A: Allocator> Iterator for Drain<'a, T, A> {
+    type Item = T;
+    
+    fn next(&mut self) -> Option<T> {
+        if self.front == self.back { return None; }
+        self.front += 1;
+        Some(unsafe { self.vec.slot_ptr(self.front - 1).read() })
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl<'a, T, A: Allocator> DoubleEndedIterator for Drain<'a, T, A> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.front == self.back { return None; }
+        self.back -= 1;
+        Some(unsafe { self.vec.slot_ptr(self.back).read() })
+    }
+}
+
+impl<'a, T, A: Allocator> ExactSizeIterator for Drain<'a, T, A> {}
+
+impl<'a, T, A: Allocator> Drop for Drain<'a, T, A> {
+    fn drop(&mut self) {
+        while self.next_back().is_some() {}
+        self.vec.trim_buffers();
+    }
+}
+
+/// Flattens an iterator over buffers into an iterator over their
+/// elements, without recomputing indices per element.
+struct FlatIter<O, I> {
+    buffers: O,
+    front: Option<I>,
+    back: Option<I>,
+    len: usize,
+}
+
+impl<O, I> FlatIter<O, I> {
+    fn new(buffers: O, len: usize) -> Self {
+        FlatIter { buffers, front: None, back: None, len }
+    }
+}
+
+impl<O, I> Iterator for FlatIter<O, I>
+where
+    O: DoubleEndedIterator,
+    O::Item: IntoIterator<IntoIter=I, Item=I::Item>,
+    I: DoubleEndedIterator,
+{
+    type Item = I::Item;
+    
+    fn next(&mut self) -> Option<I::Item> {
+        loop {
+            if let Some(elem) = self.front.as_mut().and_then(I::next) {
+                self.len -= 1;
+                return Some(elem);
+            }
+            match self.buffers.next() {
+                Some(buffer) => self.front = Some(buffer.into_iter()),
+                None => {
+                    // remaining elements, if any, are in the back
+                    let elem = self.back.as_mut().and_then(I::next)?;
+                    self.len -= 1;
+                    return Some(elem);
+                },
+            }
+        }
+    }
+    
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<O, I> DoubleEndedIterator for FlatIter<O, I>
+where
+    O: DoubleEndedIterator,
+    O::Item: IntoIterator<IntoIter=I, Item=I::Item>,
+    I: DoubleEndedIterator,
+{
+    fn next_back(&mut self) -> Option<I::Item> {
+        loop {
+            if let Some(elem) = self.back.as_mut().and_then(I::next_back) {
+                self.len -= 1;
+                return Some(elem);
+            }
+            match self.buffers.next_back() {
+                Some(buffer) => self.back = Some(buffer.into_iter()),
+                None => {
+                    let elem = self.front.as_mut().and_then(I::next_back)?;
+                    self.len -= 1;
+                    return Some(elem);
+                },
+            }
+        }
+    }
+}
+
+/// Iterator over pinned shared refs to a `PinVec`.
+pub struct Iter<'a, T, A: Allocator = Global>(
+    FlatIter<slice::Iter<'a, PinBuffer<T, A>>, buf::Iter<'a, T>>);
+
+/// Iterator over pinned mutable refs to a `PinVec`.
+pub struct IterMut<'a, T, A: Allocator = Global>(
+    FlatIter<slice::IterMut<'a, PinBuffer<T, A>>, buf::IterMut<'a, T>>);
+
+/// Owning iterator over a `PinVec` of `Unpin` elements.
+pub struct IntoIter<T, A: Allocator = Global>(
+    FlatIter<vec::IntoIter<PinBuffer<T, A>>, buf::IntoIter<T, A>>);
+
+macro_rules! impl_flat_iter {
+    ([$($g:tt)*] $ty:ty => $item:ty $(where $($w:tt)*)?)=>{
+        impl<$($g)*> Iterator for $ty $(where $($w)*)? {
+            type Item = $item;
+            
+            fn next(&mut self) -> Option<$item> { self.0.next() }
+            
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+        
+        impl<$($g)*> DoubleEndedIterator for $ty $(where $($w)*)? {
+            fn next_back(&mut self) -> Option<$item> { self.0.next_back() }
+        }
+        
+        impl<$($g)*> ExactSizeIterator for $ty $(where $($w)*)? {}
+    };
+}
+
+impl_flat_iter!(['a, T, A: Allocator] Iter<'a, T, A> => Pin<&'a T>);
+impl_flat_iter!(['a, T, A: Allocator] IterMut<'a, T, A> => Pin<&'a mut T>);
+impl_flat_iter!([T, A: Allocator] IntoIter<T, A> => T where T: Unpin);
+
+impl<T, A: Allocator> Drop for IntoIter<T, A> {
+    fn drop(&mut self) {
+        // drop the remaining elements top-first, like `PinVec` does
+        let flat = &mut self.0;
+        drop(flat.back.take());
+        while let Some(buffer) = flat.buffers.next_back() {
+            drop(buffer);
+        }
+        drop(flat.front.take());
+    }
 }
 
 
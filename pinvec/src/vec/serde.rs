@@ -0,0 +1,72 @@
+use super::PinVec;
+use core::{
+    fmt::{self, Formatter},
+    pin::Pin,
+};
+use allocator_api2::alloc::Allocator;
+use serde::{
+    de::{DeserializeSeed, SeqAccess, Visitor},
+    ser::SerializeSeq,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+
+/// Serializes as a sequence.
+impl<T: Serialize, A: Allocator> Serialize for PinVec<T, A> {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        let mut seq = s.serialize_seq(Some(self.len()))?;
+        for elem in self.iter() {
+            seq.serialize_element(Pin::get_ref(elem))?;
+        }
+        seq.end()
+    }
+}
+
+/// Deserializes from a sequence, with the default `buf_0_len`.
+///
+/// To deserialize with a different configuration, deserialize 
+/// into an existing `&mut PinVec`.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for PinVec<T> {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let mut vec = PinVec::default();
+        (&mut vec).deserialize(d)?;
+        Ok(vec)
+    }
+}
+
+/// Deserializes a sequence by pushing its elements onto the vec,
+/// which keeps its `buf_0_len`, retention policy and allocator.
+impl<'de, T, A> DeserializeSeed<'de> for &mut PinVec<T, A>
+where
+    T: Deserialize<'de>,
+    A: Allocator + Clone,
+{
+    type Value = ();
+    
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<(), D::Error> {
+        d.deserialize_seq(PushVisitor(self))
+    }
+}
+
+struct PushVisitor<'a, T, A: Allocator>(&'a mut PinVec<T, A>);
+
+impl<'a, 'de, T, A> Visitor<'de> for PushVisitor<'a, T, A>
+where
+    T: Deserialize<'de>,
+    A: Allocator + Clone,
+{
+    type Value = ();
+    
+    fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("a sequence")
+    }
+    
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<(), S::Error> {
+        while let Some(elem) = seq.next_element()? {
+            self.0.push(elem);
+        }
+        Ok(())
+    }
+}
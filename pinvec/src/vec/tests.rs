@@ -1,7 +1,18 @@
 
 use std::usize;
-use crate::PinVec;
+use crate::{
+    PinVec, Retention, Handle,
+    test_util::{DropLog, assert_drops_top_first},
+};
 use pow_of_2::PowOf2;
+use allocator_api2::alloc::{Allocator, AllocError, Global, Layout};
+use std::{
+    pin::Pin,
+    mem::MaybeUninit,
+    ptr::NonNull,
+    rc::Rc,
+    cell::Cell,
+};
 
 #[test]
 fn calc_index_precomp() {
@@ -120,3 +131,408 @@ fn push_pop_addrstable() {
         self_addr_check(&vec);
     }
 }
+
+#[test]
+fn index() {
+    for buf_0_len in pows_of_two() {
+        let mut vec: PinVec<usize> = PinVec::new(buf_0_len);
+        for i in 0..1000 {
+            vec.push(i);
+        }
+        for i in 0..1000 {
+            assert_eq!(vec[i], i);
+            vec[i] *= 2;
+        }
+        for i in 0..1000 {
+            assert_eq!(*vec.idx_ref(i), i * 2);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn index_oob() {
+    let mut vec: PinVec<usize> = PinVec::default();
+    vec.push(0);
+    let _ = vec[1];
+}
+
+#[test]
+fn iter() {
+    for buf_0_len in pows_of_two() {
+        let mut vec: PinVec<usize> = PinVec::new(buf_0_len);
+        assert_eq!(vec.iter().next(), None);
+        for i in 0..1000 {
+            vec.push(i);
+        }
+        
+        assert_eq!(vec.iter().len(), 1000);
+        assert!(vec.iter().map(|r| *r).eq(0..1000));
+        assert!(vec.iter().rev().map(|r| *r).eq((0..1000).rev()));
+        
+        // alternate ends
+        let mut iter = vec.iter();
+        for i in 0..500 {
+            assert_eq!(iter.len(), 1000 - i * 2);
+            assert_eq!(iter.next().map(|r| *r), Some(i));
+            assert_eq!(iter.next_back().map(|r| *r), Some(999 - i));
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+        
+        for mut r in &mut vec {
+            *r += 1;
+        }
+        assert!(vec.iter_mut().map(|r| *r).eq(1..1001));
+        assert!(vec.into_iter().rev().eq((1..1001).rev()));
+    }
+}
+
+#[test]
+fn checked_access() {
+    for buf_0_len in pows_of_two() {
+        let mut vec: PinVec<usize> = PinVec::new(buf_0_len);
+        assert!(vec.get_ref(0).is_none());
+        assert!(vec.get_mut(0).is_none());
+        assert!(vec.first_ref().is_none());
+        assert!(vec.last_mut().is_none());
+        assert_eq!(vec.try_set(0, 7), Err(7));
+        
+        for i in 0..100 {
+            vec.push(i);
+        }
+        assert_eq!(vec.get_ref(99).map(|r| *r), Some(99));
+        assert!(vec.get_ref(100).is_none());
+        assert!(vec.get_mut(usize::MAX).is_none());
+        assert_eq!(vec.first_ref().map(|r| *r), Some(0));
+        assert_eq!(vec.last_ref().map(|r| *r), Some(99));
+        *vec.first_mut().unwrap() = 1000;
+        *vec.last_mut().unwrap() = 1099;
+        assert_eq!(vec.try_set(50, 1050), Ok(()));
+        assert_eq!(vec.try_set(100, 1100), Err(1100));
+        assert_eq!(vec[0] + vec[50] + vec[99], 3149);
+    }
+}
+
+#[test]
+fn capacity() {
+    for buf_0_len in pows_of_two() {
+        let b = buf_0_len.to_uint();
+        let mut vec: PinVec<usize> = PinVec::with_retention(buf_0_len, Retention::KeepSpare);
+        assert_eq!(vec.capacity(), 0);
+        
+        vec.reserve(0);
+        assert_eq!(vec.capacity(), 0);
+        vec.reserve(1);
+        assert_eq!(vec.capacity(), b);
+        vec.reserve(b + 1);
+        assert_eq!(vec.capacity(), b * 3);
+        vec.reserve(b * 3 + 1);
+        assert_eq!(vec.capacity(), b * 7);
+        
+        for i in 0..b * 7 {
+            vec.push(i);
+        }
+        assert_eq!(vec.capacity(), b * 7);
+        assert!(vec.iter().map(|r| *r).eq(0..b * 7));
+        
+        // popping into the second buffer keeps the third as a spare
+        for _ in 0..b * 4 + 1 {
+            vec.remove_top();
+        }
+        assert_eq!(vec.capacity(), b * 7);
+        assert_eq!(vec.last_ref().map(|r| *r), Some(b * 3 - 2));
+        vec.shrink_to_fit();
+        assert_eq!(vec.capacity(), b * 3);
+        
+        vec.set_retention(Retention::Free);
+        while vec.pop().is_some() {}
+        assert_eq!(vec.capacity(), 0);
+        assert!(vec.last_ref().is_none());
+    }
+}
+
+/// Allocator which counts live allocations.
+#[derive(Clone, Default)]
+struct CountingAlloc(Rc<Cell<usize>>);
+
+unsafe impl Allocator for CountingAlloc {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.0.set(self.0.get() + 1);
+        Global.allocate(layout)
+    }
+    
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        self.0.set(self.0.get() - 1);
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn custom_alloc() {
+    for buf_0_len in pows_of_two() {
+        let alloc = CountingAlloc::default();
+        let log = DropLog::new();
+        let n = buf_0_len.to_uint() * 3;
+        
+        let mut vec = PinVec::with_retention_in(
+            buf_0_len, Retention::Free, alloc.clone());
+        log.fill(&mut vec, 0..n, PinVec::push);
+        assert_eq!(alloc.0.get(), 2);
+        vec.push(log.elem(n));
+        assert_eq!(alloc.0.get(), 3);
+        vec.remove_top();
+        assert_eq!(alloc.0.get(), 2);
+        log.assert_dropped_rev(n..n + 1);
+        
+        drop(vec);
+        assert_eq!(alloc.0.get(), 0);
+        log.assert_dropped_rev(0..n);
+    }
+}
+
+#[test]
+fn custom_alloc_into_iter() {
+    let alloc = CountingAlloc::default();
+    let mut vec = PinVec::new_in(PowOf2::<usize>::_4, alloc.clone());
+    for i in 0..100 {
+        vec.push(Box::new(i));
+    }
+    let mut iter = vec.into_iter();
+    assert_eq!(iter.next().map(|b| *b), Some(0));
+    assert_eq!(iter.next_back().map(|b| *b), Some(99));
+    assert!(alloc.0.get() > 0);
+    drop(iter);
+    assert_eq!(alloc.0.get(), 0);
+}
+
+#[test]
+fn truncate_clear() {
+    for buf_0_len in pows_of_two() {
+        let log = DropLog::new();
+        let mut vec = PinVec::with_retention(buf_0_len, Retention::Free);
+        log.fill(&mut vec, 0..1000, PinVec::push);
+        
+        vec.truncate(1000);
+        assert!(log.is_empty());
+        vec.truncate(100);
+        assert_eq!(vec.len(), 100);
+        log.assert_dropped_rev(100..1000);
+        assert!(vec.capacity() < 200 + buf_0_len.to_uint() * 2);
+        for (i, elem) in vec.iter().enumerate() {
+            assert_eq!(elem.val, i);
+        }
+        
+        vec.push(log.elem(1000));
+        assert_eq!(vec[100].val, 1000);
+        
+        vec.clear();
+        assert!(vec.is_empty());
+        assert_eq!(vec.capacity(), 0);
+        assert_eq!(log.vals()[0], 1000);
+        assert!(log.vals()[1..].iter().copied().eq((0..100).rev()));
+    }
+}
+
+#[test]
+fn drain() {
+    for buf_0_len in pows_of_two() {
+        let log = DropLog::new();
+        let mut vec = PinVec::new(buf_0_len);
+        log.fill(&mut vec, 0..1000, PinVec::push);
+        
+        let mut drain = vec.drain(100..);
+        assert_eq!(drain.len(), 900);
+        for i in 100..150 {
+            assert_eq!(drain.next().map(|e| e.val), Some(i));
+        }
+        assert_eq!(drain.next_back().map(|e| e.val), Some(999));
+        drop(drain);
+        assert!(log.vals().into_iter()
+            .eq((100..150).chain(Some(999)).chain((150..999).rev())));
+        assert_eq!(vec.len(), 100);
+        
+        log.fill(&mut vec, 100..200, PinVec::push);
+        for (i, elem) in vec.iter().enumerate() {
+            assert_eq!(elem.val, i);
+        }
+        assert!(vec.drain(200..).next().is_none());
+        assert_eq!(vec.drain(0..).map(|e| e.val).sum::<usize>(), 199 * 100);
+        assert!(vec.is_empty());
+    }
+}
+
+#[test]
+fn drop_order() {
+    for buf_0_len in pows_of_two() {
+        assert_drops_top_first(1000, PinVec::new(buf_0_len), PinVec::push);
+        
+        let log = DropLog::new();
+        let mut vec = PinVec::new(buf_0_len);
+        log.fill(&mut vec, 0..1000, PinVec::push);
+        let mut iter = vec.into_iter();
+        assert_eq!(iter.next().map(|e| e.val), Some(0));
+        assert_eq!(iter.next_back().map(|e| e.val), Some(999));
+        log.clear();
+        drop(iter);
+        log.assert_dropped_rev(1..999);
+    }
+}
+
+#[test]
+fn drop_order_panic() {
+    let log = DropLog::new();
+    let mut vec = PinVec::new(PowOf2::<usize>::_4);
+    for i in 0..100 {
+        vec.push(if i == 10 { log.bomb(i) } else { log.elem(i) });
+    }
+    let result = std::panic::catch_unwind(
+        std::panic::AssertUnwindSafe(move || drop(vec)));
+    assert!(result.is_err());
+    
+    // the remaining elements and buffers are still dropped top-first
+    log.assert_dropped_rev(0..100);
+}
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PinVec<String>>();
+}
+
+/// Node which stores its own address.
+struct SelfRef {
+    val: usize,
+    this: *const SelfRef,
+    _pin: std::marker::PhantomPinned,
+}
+
+fn init_self_ref(
+    slot: Pin<&mut MaybeUninit<SelfRef>>,
+    val: usize,
+) -> Pin<&mut SelfRef> {
+    unsafe {
+        let slot = slot.get_unchecked_mut();
+        let this = slot.as_ptr();
+        slot.write(SelfRef { val, this, _pin: std::marker::PhantomPinned });
+        Pin::new_unchecked(slot.assume_init_mut())
+    }
+}
+
+#[test]
+fn push_with() {
+    for buf_0_len in pows_of_two() {
+        let mut vec = PinVec::new(buf_0_len);
+        for i in 0..1000 {
+            let elem = vec.push_with(|slot| init_self_ref(slot, i));
+            assert_eq!(elem.val, i);
+        }
+        assert_eq!(vec.len(), 1000);
+        for (i, elem) in vec.iter().enumerate() {
+            assert_eq!(elem.val, i);
+            assert_eq!(elem.this, &*elem as *const SelfRef);
+        }
+    }
+}
+
+#[test]
+fn push_with_panic() {
+    let mut vec = PinVec::new(PowOf2::<usize>::_4);
+    vec.push_with(|slot| init_self_ref(slot, 0));
+    
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        vec.push_with(|_| panic!("init failed"));
+    }));
+    assert!(result.is_err());
+    assert_eq!(vec.len(), 1);
+    
+    // returning a reference to anything but the slot
+    let other = Box::into_raw(Box::new(0u32));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut ints: PinVec<u32> = PinVec::new(PowOf2::<usize>::_4);
+        ints.push_with(|_| Pin::new(unsafe { &mut *other }));
+        ints.len()
+    }));
+    assert!(result.is_err());
+    drop(unsafe { Box::from_raw(other) });
+    
+    vec.push_with(|slot| init_self_ref(slot, 1));
+    assert!(vec.iter().map(|e| e.val).eq(0..2));
+}
+
+#[test]
+fn handles() {
+    for buf_0_len in pows_of_two() {
+        let mut vec = PinVec::new(buf_0_len);
+        let handles: Vec<Handle<usize>> = (0..100)
+            .map(|i| vec.push_handle(i))
+            .collect();
+        for (i, &handle) in handles.iter().enumerate() {
+            assert_eq!(handle.index(), i);
+            assert_eq!(vec.get_by_handle(handle).map(|r| *r), Some(i));
+            assert_eq!(vec.handle(i), Some(handle));
+        }
+        
+        // reusing an index after removal
+        vec.remove_top();
+        let h99 = vec.push_handle(1000);
+        assert_eq!(h99.index(), 99);
+        assert!(!vec.contains_handle(handles[99]));
+        assert!(vec.get_by_handle(handles[99]).is_none());
+        assert_eq!(vec.get_by_handle(h99).map(|r| *r), Some(1000));
+        
+        // overwriting
+        vec.set(50, 500);
+        assert!(vec.get_by_handle(handles[50]).is_none());
+        *vec.get_mut_by_handle(handles[49]).unwrap() += 1;
+        assert_eq!(vec[49], 50);
+        
+        // writes through mutable references are not tracked
+        vec.idx_mut(48).set(480);
+        assert_eq!(vec.get_by_handle(handles[48]).map(|r| *r), Some(480));
+        
+        // removed entirely
+        vec.truncate(10);
+        assert!(vec.get_by_handle(handles[20]).is_none());
+        assert!(vec.get_mut_by_handle(h99).is_none());
+        assert!(vec.handle(10).is_none());
+        vec.push(10);
+        vec.drain(5..);
+        vec.push(5);
+        assert!(vec.get_by_handle(handles[5]).is_none());
+        assert!(vec.contains_handle(handles[4]));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    use serde::de::DeserializeSeed;
+    use serde_test::{Token, assert_ser_tokens};
+    
+    let mut vec = PinVec::new(PowOf2::<usize>::_2);
+    for i in 0..3u32 {
+        vec.push(i);
+    }
+    assert_ser_tokens(&vec, &[
+        Token::Seq { len: Some(3) },
+        Token::U32(0),
+        Token::U32(1),
+        Token::U32(2),
+        Token::SeqEnd,
+    ]);
+    
+    let json = serde_json::to_string(&vec).unwrap();
+    assert_eq!(json, "[0,1,2]");
+    let vec: PinVec<u32> = serde_json::from_str(&json).unwrap();
+    assert!(vec.iter().map(|r| *r).eq(0..3));
+    
+    // deserializing into an existing vec keeps its buf_0_len
+    let json = serde_json::to_string(&(0..10).collect::<Vec<u32>>()).unwrap();
+    let mut vec = PinVec::new(PowOf2::<usize>::_4);
+    vec.push(100);
+    (&mut vec).deserialize(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+    assert_eq!(vec.len(), 11);
+    assert_eq!(vec.capacity(), 4 + 8);
+    assert!(vec.iter().map(|r| *r).eq(Some(100).into_iter().chain(0..10)));
+}
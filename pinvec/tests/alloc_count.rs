@@ -0,0 +1,94 @@
+//! Allocation counts of `PinVec` capacity management, measured
+//! with a counting global allocator.
+//!
+//! Counts are kept per thread, so that tests running in parallel
+//! don't observe each other's allocations.
+
+use pinvec::{PinVec, Retention};
+use pow_of_2::PowOf2;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Number of allocations made by this thread while running `f`.
+fn count_allocs<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCS.with(Cell::get);
+    f();
+    ALLOCS.with(Cell::get) - before
+}
+
+/// Vec with its first buffer full, and allocation for the
+/// outer vector of buffers already made.
+fn full_first_buffer(retention: Retention) -> PinVec<u64> {
+    let mut vec = PinVec::with_retention(PowOf2::<usize>::_4, retention);
+    vec.reserve(4);
+    for i in 0..4 {
+        vec.push(i);
+    }
+    vec
+}
+
+#[test]
+fn oscillate_free() {
+    let mut vec = full_first_buffer(Retention::Free);
+    let allocs = count_allocs(|| for i in 0..100 {
+        vec.push(i);
+        vec.remove_top();
+    });
+    assert_eq!(allocs, 100);
+}
+
+#[test]
+fn oscillate_keep_spare() {
+    let mut vec = full_first_buffer(Retention::KeepSpare);
+    let allocs = count_allocs(|| for i in 0..100 {
+        vec.push(i);
+        vec.remove_top();
+    });
+    assert_eq!(allocs, 1);
+}
+
+#[test]
+fn reserve() {
+    let mut vec = PinVec::new(PowOf2::<usize>::_4);
+    vec.reserve(1000);
+    assert!(vec.capacity() >= 1000);
+    let allocs = count_allocs(|| for i in 0..1000u64 {
+        vec.push(i);
+    });
+    assert_eq!(allocs, 0);
+}
+
+#[test]
+fn shrink_to_fit() {
+    let mut vec = PinVec::new(PowOf2::<usize>::_4);
+    vec.reserve(1000);
+    vec.push(0u64);
+    vec.shrink_to_fit();
+    assert_eq!(vec.capacity(), 4);
+    let allocs = count_allocs(|| for i in 1..5 {
+        vec.push(i);
+    });
+    assert_eq!(allocs, 1);
+}
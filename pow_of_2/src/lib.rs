@@ -7,10 +7,15 @@
 //! arithmetic operators with other, simpler artithmetic operators.
 
 #[cfg(test)]
+#[allow(clippy::legacy_numeric_constants, clippy::manual_bits)]
 mod tests;
 
+/// Serde representation of `PowOf2` as its exponent, for use
+/// with `#[serde(with = "pow_of_2::serde_exp")]`.
+#[cfg(feature = "serde")]
+pub mod serde_exp;
+
 use core::{
-    u8,
     ops::{Shl, Mul, Div, MulAssign, DivAssign},
     mem::size_of,
     fmt::{self, Formatter, Display, Debug},
@@ -110,6 +115,50 @@ impl<T: UInt> PowOf2<T> {
 }
 
 
+// ==== rounding ====
+
+macro_rules! impl_floor {
+    ($($t:ty),*)=>{$(
+        impl PowOf2<$t> {
+            /// Greatest power of 2 not above `n`.
+            ///
+            /// Fails if `n` is 0.
+            #[inline(always)]
+            pub fn floor(n: $t) -> Option<Self> {
+                match n {
+                    0 => None,
+                    n => Some(PowOf2 {
+                        exp: (<$t>::BITS - 1 - n.leading_zeros()) as u8,
+                        p: PhantomData,
+                    }),
+                }
+            }
+        }
+    )*};
+}
+impl_floor!(usize, u8, u16, u32, u64, u128);
+
+
+// ==== conversion ====
+
+macro_rules! impl_try_from_uint {
+    ($($t:ty),*)=>{$(
+        impl PowOf2<$t> {
+            /// Exactly `n`.
+            ///
+            /// Fails if `n` is not a power of 2.
+            #[inline(always)]
+            pub fn try_from_uint(n: $t) -> Option<Self> {
+                if n.is_power_of_two() {
+                    PowOf2::<$t>::floor(n)
+                } else { None }
+            }
+        }
+    )*};
+}
+impl_try_from_uint!(usize, u8, u16, u32, u64, u128);
+
+
 // ==== type-enhanced arithmetic ====
 
 
@@ -285,4 +334,34 @@ impl Display for Two {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result { 
         Display::fmt(&2, f)
     }
-}
\ No newline at end of file
+}
+
+
+// ==== serde ====
+
+/// Serializes as the integer value. See `serde_exp` to serialize
+/// as the exponent instead.
+#[cfg(feature = "serde")]
+impl<T: UInt + serde::Serialize> serde::Serialize for PowOf2<T> {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.to_uint().serialize(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+macro_rules! impl_deserialize {
+    ($($t:ty),*)=>{$(
+        /// Deserializes from the integer value, rejecting 
+        /// non-powers of 2.
+        impl<'de> serde::Deserialize<'de> for PowOf2<$t> {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                let n = <$t>::deserialize(d)?;
+                PowOf2::<$t>::try_from_uint(n)
+                    .ok_or_else(|| serde::de::Error::custom(format_args!(
+                        "{} is not a power of 2", n)))
+            }
+        }
+    )*};
+}
+#[cfg(feature = "serde")]
+impl_deserialize!(usize, u8, u16, u32, u64, u128);
@@ -0,0 +1,22 @@
+use crate::{PowOf2, UInt};
+use core::any::type_name;
+use serde::{
+    de::Error,
+    Deserialize,
+    Deserializer,
+    Serializer,
+};
+
+/// Serialize as the exponent.
+pub fn serialize<T: UInt, S: Serializer>(n: &PowOf2<T>, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u8(n.exp())
+}
+
+/// Deserialize from the exponent, rejecting exponents beyond 
+/// `T`'s domain.
+pub fn deserialize<'de, T: UInt, D: Deserializer<'de>>(d: D) -> Result<PowOf2<T>, D::Error> {
+    let exp = u8::deserialize(d)?;
+    PowOf2::try_from_exp(exp)
+        .ok_or_else(|| D::Error::custom(format_args!(
+            "exponent {} beyond domain of {}", exp, type_name::<T>())))
+}
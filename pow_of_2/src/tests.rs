@@ -109,3 +109,72 @@ fn mul_oob_63() {
 fn mul_oob_32() {
     let _: PowOf2<usize> = PowOf2::from_exp(SBITS as u8 / 2) * PowOf2::from_exp(SBITS as u8 / 2);
 }
+
+#[test]
+fn floor() {
+    assert_eq!(PowOf2::<usize>::floor(0), None);
+    assert_eq!(PowOf2::<u8>::floor(u8::MAX), Some(PowOf2::from_exp(7)));
+    for n in 1..10000usize {
+        let f = PowOf2::<usize>::floor(n).unwrap().to_uint();
+        assert!(f <= n && n < f * 2);
+    }
+    for e in 0..SBITS {
+        let n: PowOf2<usize> = PowOf2::from_exp(e as u8);
+        assert_eq!(PowOf2::<usize>::floor(n.to_uint()), Some(n));
+    }
+}
+
+#[test]
+fn try_from_uint() {
+    assert_eq!(PowOf2::<usize>::try_from_uint(0), None);
+    assert_eq!(PowOf2::<u8>::try_from_uint(128), Some(PowOf2::from_exp(7)));
+    for n in 1..10000usize {
+        assert_eq!(
+            PowOf2::<usize>::try_from_uint(n).is_some(),
+            n.is_power_of_two()
+        );
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use crate::{PowOf2, serde_exp};
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use serde_test::{
+        Token,
+        assert_tokens,
+        assert_de_tokens_error,
+    };
+    
+    #[derive(Debug, PartialEq)]
+    struct Exp(PowOf2<u8>);
+    
+    impl Serialize for Exp {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            serde_exp::serialize(&self.0, s)
+        }
+    }
+    
+    impl<'de> Deserialize<'de> for Exp {
+        fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            serde_exp::deserialize(d).map(Exp)
+        }
+    }
+    
+    #[test]
+    fn value() {
+        assert_tokens(&PowOf2::<u32>::from_exp(3), &[Token::U32(8)]);
+        assert_tokens(&PowOf2::<u64>::from_exp(0), &[Token::U64(1)]);
+        assert_de_tokens_error::<PowOf2<u32>>(
+            &[Token::U32(6)], "6 is not a power of 2");
+        assert_de_tokens_error::<PowOf2<u32>>(
+            &[Token::U32(0)], "0 is not a power of 2");
+    }
+    
+    #[test]
+    fn exp() {
+        assert_tokens(&Exp(PowOf2::from_exp(7)), &[Token::U8(7)]);
+        assert_de_tokens_error::<Exp>(
+            &[Token::U8(8)], "exponent 8 beyond domain of u8");
+    }
+}